@@ -1,97 +1,104 @@
-use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use anyhow::Result;
+use futures::stream::{BoxStream, StreamExt};
+use serde_json::json;
 
 use crate::models::{AIReview, CodeQuality, Lesson};
+use crate::providers::{ChatMessage, LlmProvider};
 
-#[derive(Debug, Clone)]
-pub struct AIAssistant {
-    api_key: Option<String>,
-    client: reqwest::Client,
-}
-
-#[derive(Debug, Serialize)]
-struct ClaudeRequest {
-    model: String,
-    max_tokens: u32,
-    messages: Vec<ClaudeMessage>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ClaudeMessage {
-    role: String,
-    content: String,
+/// One event emitted while streaming a code review: either the next chunk of
+/// feedback text, or the fully parsed review once the model has finished.
+pub enum ReviewStreamEvent {
+    Delta(String),
+    Done(Box<AIReview>),
 }
 
-#[derive(Debug, Deserialize)]
-struct ClaudeResponse {
-    content: Vec<ClaudeContent>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ClaudeContent {
-    text: String,
+#[derive(Clone)]
+pub struct AIAssistant {
+    provider: std::sync::Arc<dyn LlmProvider>,
 }
 
 impl AIAssistant {
-    pub fn new(api_key: Option<String>) -> Self {
+    pub fn new(provider: Box<dyn LlmProvider>) -> Self {
         Self {
-            api_key,
-            client: reqwest::Client::new(),
+            provider: std::sync::Arc::from(provider),
         }
     }
 
+    /// Reviews the student's code. Prefers asking the provider to call the
+    /// `submit_review` tool, which returns `AIReview`'s fields directly and
+    /// can't be thrown off by unusual phrasing in the feedback prose. Falls
+    /// back to the plain-text completion (and the old keyword-based parser)
+    /// if the provider doesn't support tool calling or replies with prose
+    /// instead of invoking the tool.
     pub async fn review_code(&self, code: &str, lesson: &Lesson) -> Result<AIReview> {
-        // If no API key is provided, use a simple rule-based review
-        if self.api_key.is_none() {
-            return self.simple_review(code, lesson);
-        }
-
         let prompt = self.build_review_prompt(code, lesson);
-
-        let api_key = self.api_key.as_ref().unwrap();
-
-        let request = ClaudeRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 1024,
-            messages: vec![ClaudeMessage {
-                role: "user".to_string(),
-                content: prompt,
-            }],
-        };
-
-        let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send request to Claude API")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            tracing::warn!("Claude API error ({}): {}", status, error_text);
-            return self.simple_review(code, lesson);
+        let messages = [ChatMessage::user(prompt)];
+
+        let tool_call = self
+            .provider
+            .complete_tool(
+                &messages,
+                1024,
+                "submit_review",
+                "Submit a structured code review for the student's submission.",
+                review_tool_schema(),
+            )
+            .await?;
+
+        if let Some(review) = tool_call.and_then(|input| serde_json::from_value(input).ok()) {
+            return Ok(review);
         }
 
-        let claude_response: ClaudeResponse = response
-            .json()
-            .await
-            .context("Failed to parse Claude API response")?;
-
-        let feedback = claude_response
-            .content
-            .first()
-            .map(|c| c.text.clone())
-            .unwrap_or_else(|| "No feedback available".to_string());
+        let feedback = self.provider.complete(&messages, 1024).await?;
 
-        // Parse the response to extract structured feedback
         self.parse_ai_feedback(&feedback, lesson)
     }
 
+    /// Streams the review as incremental feedback text, followed by a final
+    /// event carrying the fully parsed `AIReview` once the model is done.
+    pub async fn review_code_stream(
+        &self,
+        code: &str,
+        lesson: Lesson,
+    ) -> Result<BoxStream<'static, Result<ReviewStreamEvent>>> {
+        let prompt = self.build_review_prompt(code, &lesson);
+
+        let deltas = self
+            .provider
+            .complete_stream(&[ChatMessage::user(prompt)], 1024)
+            .await?;
+
+        let state = (deltas, String::new(), self.clone(), lesson, false);
+
+        let stream = futures::stream::unfold(
+            state,
+            |(mut deltas, mut acc, assistant, lesson, done)| async move {
+                if done {
+                    return None;
+                }
+
+                match deltas.next().await {
+                    Some(Ok(chunk)) => {
+                        acc.push_str(&chunk);
+                        Some((
+                            Ok(ReviewStreamEvent::Delta(chunk)),
+                            (deltas, acc, assistant, lesson, false),
+                        ))
+                    }
+                    Some(Err(e)) => Some((Err(e), (deltas, acc, assistant, lesson, true))),
+                    None => {
+                        let result = assistant
+                            .parse_ai_feedback(&acc, &lesson)
+                            .map(|review| ReviewStreamEvent::Done(Box::new(review)));
+                        Some((result, (deltas, acc, assistant, lesson, true)))
+                    }
+                }
+            },
+        );
+
+        Ok(stream.boxed())
+    }
+
     fn build_review_prompt(&self, code: &str, lesson: &Lesson) -> String {
         format!(
             r#"You are a helpful Python programming tutor. Review the following student code for a lesson.
@@ -152,50 +159,29 @@ Keep your feedback encouraging and constructive. Focus on helping the student le
             follows_lesson_objectives: follows_objectives,
         })
     }
+}
 
-    fn simple_review(&self, code: &str, lesson: &Lesson) -> Result<AIReview> {
-        let mut suggestions = Vec::new();
-        let mut quality_score = 0;
-
-        // Simple heuristics
-        if code.len() < 10 {
-            suggestions.push("Your code seems quite short. Make sure you've completed all the requirements.".to_string());
-        } else {
-            quality_score += 1;
-        }
-
-        if !code.contains("def ") && lesson.objectives.iter().any(|obj| obj.to_lowercase().contains("function")) {
-            suggestions.push("This lesson requires defining a function. Consider using 'def' to create one.".to_string());
-        } else if code.contains("def ") {
-            quality_score += 1;
-        }
-
-        if code.lines().count() > 1 {
-            quality_score += 1;
-        }
-
-        let code_quality = match quality_score {
-            3 => CodeQuality::Excellent,
-            2 => CodeQuality::Good,
-            1 => CodeQuality::NeedsImprovement,
-            _ => CodeQuality::Poor,
-        };
-
-        let feedback = format!(
-            "Code review for '{}': Your code has been submitted. {}",
-            lesson.title,
-            if suggestions.is_empty() {
-                "Keep up the good work!"
-            } else {
-                "Here are some suggestions to improve your code."
-            }
-        );
-
-        Ok(AIReview {
-            feedback,
-            suggestions,
-            code_quality,
-            follows_lesson_objectives: quality_score >= 2,
-        })
-    }
+/// JSON schema for the `submit_review` tool, matching `AIReview` field for
+/// field so a forced tool call's `input` can be deserialized straight into it.
+fn review_tool_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "feedback": {
+                "type": "string",
+                "description": "Overall feedback on the code's quality and correctness."
+            },
+            "suggestions": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Specific, actionable suggestions for improvement."
+            },
+            "code_quality": {
+                "type": "string",
+                "enum": ["Excellent", "Good", "NeedsImprovement", "Poor"]
+            },
+            "follows_lesson_objectives": { "type": "boolean" }
+        },
+        "required": ["feedback", "suggestions", "code_quality", "follows_lesson_objectives"]
+    })
 }
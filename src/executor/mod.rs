@@ -8,7 +8,7 @@ use futures::StreamExt;
 use std::time::Instant;
 use uuid::Uuid;
 
-use crate::models::ExecutionResult;
+use crate::models::{Diagnostic, DiagnosticSeverity, ExecutionResult, RunMode, TestCaseResult};
 
 pub struct CodeExecutor {
     docker: Docker,
@@ -123,21 +123,187 @@ impl CodeExecutor {
             .context("Failed to remove container")?;
 
         let success = error_str.is_empty();
+        let diagnostics = if success {
+            Vec::new()
+        } else {
+            parse_python_diagnostics(&error_str)
+        };
 
         Ok(ExecutionResult {
             success,
             output: output_str,
             error: if error_str.is_empty() { None } else { Some(error_str) },
             execution_time_ms,
+            diagnostics,
+            test_results: Vec::new(),
         })
     }
 
-    pub async fn execute_with_tests(&self, code: &str, test_code: &str) -> Result<ExecutionResult> {
-        let combined_code = format!("{}\n\n{}", code, test_code);
-        self.execute_code(&combined_code, 10).await
+    /// Runs `test_code` against `code` inside a generated harness that
+    /// reports each test function's outcome individually instead of a single
+    /// pass/fail, so the frontend can show which assertions failed. `mode`
+    /// controls whether only a quick subset or the full suite runs.
+    pub async fn execute_with_tests(
+        &self,
+        code: &str,
+        test_code: &str,
+        mode: RunMode,
+    ) -> Result<ExecutionResult> {
+        let harness = build_test_harness(code, test_code, mode);
+        let mut result = self.execute_code(&harness, 10).await?;
+
+        let test_results = parse_test_case_results(&result.output);
+        if !test_results.is_empty() {
+            result.output = result
+                .output
+                .lines()
+                .filter(|line| !line.starts_with("##CASE##"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            result.success = result.success && test_results.iter().all(|case| case.passed);
+        }
+        result.test_results = test_results;
+
+        Ok(result)
     }
 }
 
+/// Wraps the student's `code` and the exercise's `test_code` in a runner
+/// that executes each `test_*` function in isolation, catching assertion
+/// failures per case and printing a `##CASE##name|STATUS|expected|actual|stderr`
+/// sentinel line for each one. Test functions should call the injected
+/// `assert_equal(actual, expected)` helper (rather than a bare `assert`) so
+/// the expected/actual values can be recovered; a bare `assert` still counts
+/// as pass/fail, with no expected value and its assertion message (if any)
+/// reported as `actual`.
+fn build_test_harness(code: &str, test_code: &str, mode: RunMode) -> String {
+    let limit = match mode {
+        RunMode::Test => "1",
+        RunMode::Submit => "None",
+    };
+
+    format!(
+        r###"{code}
+
+{test_code}
+
+import traceback as __traceback
+
+
+class __CaseFailure(AssertionError):
+    def __init__(self, expected, actual):
+        self.expected = expected
+        self.actual = actual
+        super().__init__(f"expected {{expected!r}}, got {{actual!r}}")
+
+
+def assert_equal(actual, expected):
+    if actual != expected:
+        raise __CaseFailure(expected, actual)
+
+
+__test_names = sorted(
+    __name for __name, __value in list(globals().items())
+    if __name.startswith("test_") and callable(__value)
+)
+__limit = {limit}
+if __limit is not None:
+    __test_names = __test_names[:__limit]
+
+for __name in __test_names:
+    try:
+        globals()[__name]()
+        print(f"##CASE##{{__name}}|PASS|||")
+    except __CaseFailure as __e:
+        print(f"##CASE##{{__name}}|FAIL|{{__e.expected!r}}|{{__e.actual!r}}|")
+    except AssertionError as __e:
+        print(f"##CASE##{{__name}}|FAIL||{{__e}}|")
+    except Exception as __e:
+        print(f"##CASE##{{__name}}|ERROR||{{__e}}|{{__traceback.format_exc()!r}}")
+"###,
+        code = code,
+        test_code = test_code,
+        limit = limit,
+    )
+}
+
+/// Parses the `##CASE##` sentinel lines emitted by [`build_test_harness`]
+/// back into structured [`TestCaseResult`]s.
+fn parse_test_case_results(output: &str) -> Vec<TestCaseResult> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("##CASE##"))
+        .filter_map(|rest| {
+            let mut parts = rest.splitn(5, '|');
+            let name = parts.next()?.to_string();
+            let status = parts.next()?;
+            let expected = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+            let actual = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+            let stderr = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+
+            Some(TestCaseResult {
+                name,
+                passed: status == "PASS",
+                expected,
+                actual,
+                stderr,
+            })
+        })
+        .collect()
+}
+
+/// Parses a Python traceback's tail into a single `Diagnostic` so the
+/// frontend can highlight the failing line the way an LSP would, without
+/// losing the raw `error` string callers already rely on.
+///
+/// Finds the last `File "<string>", line N` frame for the line number, takes
+/// the last non-indented line (e.g. `SyntaxError: invalid syntax`) for the
+/// exception class and message, and for syntax errors recovers the column
+/// from the caret (`^`) marker line.
+fn parse_python_diagnostics(stderr: &str) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = stderr.lines().collect();
+
+    let Some(line) = lines.iter().rev().find_map(|line| {
+        line.trim_start()
+            .strip_prefix("File \"<string>\", line ")
+            .and_then(|rest| rest.split(',').next())
+            .and_then(|n| n.trim().parse::<u32>().ok())
+    }) else {
+        return Vec::new();
+    };
+
+    let Some(exception_line) = lines
+        .iter()
+        .rev()
+        .find(|line| !line.is_empty() && !line.starts_with(char::is_whitespace))
+    else {
+        return Vec::new();
+    };
+
+    let (rule, message) = match exception_line.split_once(": ") {
+        Some((rule, message)) => (Some(rule.to_string()), message.to_string()),
+        None => (None, exception_line.to_string()),
+    };
+
+    let is_syntax_error = matches!(rule.as_deref(), Some("SyntaxError") | Some("IndentationError"));
+    let column = is_syntax_error
+        .then(|| {
+            lines
+                .iter()
+                .find(|line| line.trim_end().ends_with('^'))
+                .map(|line| line.chars().take_while(|c| *c == ' ').count() as u32 + 1)
+        })
+        .flatten();
+
+    vec![Diagnostic {
+        severity: DiagnosticSeverity::Error,
+        line,
+        column,
+        message,
+        rule,
+    }]
+}
+
 impl Default for CodeExecutor {
     fn default() -> Self {
         Self::new().expect("Failed to create CodeExecutor")
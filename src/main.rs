@@ -2,6 +2,8 @@ mod ai_assistant;
 mod executor;
 mod lessons;
 mod models;
+mod providers;
+mod store;
 mod tutor;
 mod web;
 
@@ -12,7 +14,9 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use ai_assistant::AIAssistant;
 use executor::CodeExecutor;
 use lessons::LessonManager;
-use tutor::InteractiveTutor;
+use providers::{NullProvider, ProviderConfig};
+use store::{SessionStore, SqliteStore};
+use tutor::{CompletionProvider, InteractiveTutor, LlmCompletionProvider, NullCompletionProvider};
 use web::{create_router, AppState};
 
 #[tokio::main]
@@ -39,20 +43,63 @@ async fn main() -> Result<()> {
 
     let lesson_manager = Arc::new(lesson_manager);
 
-    // Initialize AI assistant (checks for ANTHROPIC_API_KEY env var)
-    let api_key = std::env::var("ANTHROPIC_API_KEY").ok();
-    if api_key.is_some() {
-        tracing::info!("Interactive AI tutor initialized with API key");
-    } else {
-        tracing::info!("Interactive AI tutor initialized in simple mode (no API key found)");
-    }
-    let ai_assistant = AIAssistant::new(api_key.clone());
+    // Initialize persistence (defaults to a local SQLite file; override with DATABASE_URL)
+    let database_url =
+        std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://tutor.db?mode=rwc".to_string());
+    let store: Arc<dyn SessionStore> = Arc::new(SqliteStore::connect(&database_url).await?);
+    tracing::info!("Connected to session store at {}", database_url);
 
-    // Initialize interactive tutor
+    // Initialize AI assistant (checks ANTHROPIC_API_KEY/OPENAI_API_KEY/OLLAMA_API_BASE env vars)
+    let provider_config = ProviderConfig::from_env();
+    let provider = match provider_config {
+        Some(config) => {
+            tracing::info!("Interactive AI tutor initialized with a configured LLM provider");
+            config.build()
+        }
+        None => {
+            tracing::info!("Interactive AI tutor initialized in simple mode (no provider configured)");
+            Box::new(NullProvider)
+        }
+    };
+    let ai_assistant = AIAssistant::new(provider);
+
+    // Initialize interactive tutor (same env vars as the AI assistant above,
+    // adapted to the tutor's single-prompt CompletionProvider via
+    // LlmCompletionProvider)
+    let tutor_provider_config = ProviderConfig::from_env();
+    let tutor_provider: Box<dyn CompletionProvider> = match tutor_provider_config {
+        Some(config) => {
+            tracing::info!("Interactive tutor initialized with a configured completion provider");
+            Box::new(LlmCompletionProvider::new(config.build()))
+        }
+        None => {
+            tracing::info!("Interactive tutor initialized in simple mode (no provider configured)");
+            Box::new(NullCompletionProvider)
+        }
+    };
+    #[cfg(feature = "voice")]
+    let interactive_tutor = {
+        let voice_config = tutor::voice::VoiceConfig::from_env().await;
+        if voice_config.is_some() {
+            tracing::info!("Voice subsystem initialized (AWS Polly narration + Transcribe ASR)");
+        } else {
+            tracing::info!("Voice subsystem disabled (set VOICE_ENABLED=1 to enable)");
+        }
+        InteractiveTutor::new(
+            tutor_provider,
+            lesson_manager.clone(),
+            code_executor.clone(),
+            store,
+            voice_config,
+        )
+    };
+
+    #[cfg(not(feature = "voice"))]
     let interactive_tutor = InteractiveTutor::new(
-        api_key,
+        tutor_provider,
         lesson_manager.clone(),
         code_executor.clone(),
+        store,
     );
 
     // Create app state
@@ -74,7 +121,11 @@ async fn main() -> Result<()> {
     tracing::info!("ðŸš€ Server running at http://{}", addr);
     tracing::info!("ðŸ“š Open http://localhost:{}/static/index.html to start learning!", port);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
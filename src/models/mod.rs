@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,11 @@ pub struct Lesson {
     pub exercises: Vec<Exercise>,
     #[serde(default)]
     pub hints: Vec<String>,
+    /// Per-lesson raw Jinja source overriding one of the tutor's named
+    /// prompt templates (keyed by template name, e.g. "teaching"), for
+    /// lessons that need a different tone or framing than the default.
+    #[serde(default)]
+    pub prompt_overrides: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +46,47 @@ pub struct ExecutionResult {
     pub output: String,
     pub error: Option<String>,
     pub execution_time_ms: u64,
+    // Optional for backward compatibility
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+    #[serde(default)]
+    pub test_results: Vec<TestCaseResult>,
+}
+
+/// The outcome of a single test case run inside the executor's test harness.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub passed: bool,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub stderr: Option<String>,
+}
+
+/// Mirrors leetcode-cli's `Run`/`Submit` split: `Test` runs a quick subset of
+/// an exercise's test cases, `Submit` runs the full suite.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum RunMode {
+    Test,
+    #[default]
+    Submit,
+}
+
+/// A single issue extracted from a Python traceback, positioned the way an
+/// LSP would so an editor can render an inline squiggle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub message: String,
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +135,21 @@ pub struct Challenge {
     pub walkthrough_used: bool,
 }
 
+/// A "Learning Moment" distilled from a mistake the student made (a failed
+/// execution or a not-yet-mastered challenge), reviewed later using spaced
+/// repetition so the correction sticks instead of just scrolling away.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub id: Uuid,
+    pub concept: String,
+    pub front: String,
+    pub back: String,
+    pub due_at: u64,
+    pub interval_days: u32,
+    pub ease: f64,
+    pub repetitions: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: MessageRole,
@@ -108,9 +170,23 @@ pub struct TutorRequest {
     pub lesson_id: String,
     pub message: Option<String>,
     pub code: Option<String>,
+    #[serde(default)]
+    pub exercise_id: Option<String>,
+    /// Set on a `ReviewFlashcards` request to grade the card the student just
+    /// reviewed; omitted when just fetching the cards due for review.
+    #[serde(default)]
+    pub flashcard_review: Option<FlashcardReview>,
     pub request_type: TutorRequestType,
 }
 
+/// A student's self-graded recall (0-5, per the SM-2 algorithm) of a single
+/// flashcard, submitted alongside a `ReviewFlashcards` request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashcardReview {
+    pub flashcard_id: Uuid,
+    pub grade: u8,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TutorRequestType {
     StartLesson,
@@ -118,6 +194,7 @@ pub enum TutorRequestType {
     SubmitCode,
     RequestHint,
     RequestWalkthrough,
+    ReviewFlashcards,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,4 +203,31 @@ pub struct TutorResponse {
     pub phase: TeachingPhase,
     pub code_result: Option<ExecutionResult>,
     pub show_new_challenge: bool,
+    /// Flashcards due for review (oldest first). Only populated by a
+    /// `ReviewFlashcards` request; empty otherwise.
+    #[serde(default)]
+    pub flashcards_due: Vec<Flashcard>,
+    /// Narrated `message` as MP3 audio, present only when the `voice`
+    /// feature is enabled and a narrator is configured.
+    #[cfg(feature = "voice")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Vec<u8>>,
+    /// Word-level timing for `audio`, so a UI can highlight along as it's
+    /// spoken. Empty unless `audio` is set.
+    #[cfg(feature = "voice")]
+    #[serde(default)]
+    pub speech_marks: Vec<SpeechMark>,
+}
+
+/// One word's timing within a narrated `TutorResponse.audio`, matching the
+/// shape of a Polly speech mark event.
+#[cfg(feature = "voice")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechMark {
+    pub time: u64,
+    #[serde(rename = "type")]
+    pub mark_type: String,
+    pub start: u32,
+    pub end: u32,
+    pub value: String,
 }
@@ -0,0 +1,784 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+/// A single turn in a chat-style completion request, independent of any
+/// particular vendor's wire format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A backend capable of turning a conversation into a completion. Every
+/// concrete LLM integration (Anthropic, OpenAI-compatible, Ollama, ...)
+/// implements this so callers never need to know which vendor is behind it.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    async fn complete(&self, messages: &[ChatMessage], max_tokens: u32) -> Result<String>;
+
+    /// Streams the completion as a sequence of incremental text deltas.
+    /// Providers that don't support native streaming can fall back to this
+    /// default, which just awaits the full completion and yields it as a
+    /// single chunk.
+    async fn complete_stream(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: u32,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let text = self.complete(messages, max_tokens).await?;
+        Ok(stream::once(async move { Ok(text) }).boxed())
+    }
+
+    /// Requests a single forced tool call and returns the model's `input`
+    /// object verbatim so callers can `serde_json::from_value` it into a
+    /// typed struct. The default implementation returns `Ok(None)` for
+    /// providers that don't support tool calling; callers should treat that
+    /// the same as the model replying with prose instead of invoking the
+    /// tool, and fall back to parsing a plain-text completion.
+    async fn complete_tool(
+        &self,
+        _messages: &[ChatMessage],
+        _max_tokens: u32,
+        _tool_name: &str,
+        _tool_description: &str,
+        _tool_schema: serde_json::Value,
+    ) -> Result<Option<serde_json::Value>> {
+        Ok(None)
+    }
+
+    /// Advances one step of an agentic, tool-calling conversation: `history`
+    /// is every turn exchanged so far (starting with a single user turn),
+    /// `tools` describes what the model may invoke. Returns the model's
+    /// plain-text reply once it's done, or the tool calls it wants run next -
+    /// callers should run each one, append its `AgentTurn::tool_results` to
+    /// `history`, and call this again until a `Text` step comes back.
+    ///
+    /// Providers that don't support tool calling can rely on this default,
+    /// which just completes the last user turn's text as a plain prompt and
+    /// never asks for a tool call.
+    async fn complete_agent_step(
+        &self,
+        history: &[AgentTurn],
+        _tools: &[ToolDefinition],
+    ) -> Result<AgentStep> {
+        let prompt = history
+            .iter()
+            .rev()
+            .find(|turn| turn.role == "user")
+            .map(|turn| turn.text())
+            .unwrap_or_default();
+
+        Ok(AgentStep::Text(
+            self.complete(&[ChatMessage::user(prompt)], 2048).await?,
+        ))
+    }
+}
+
+/// A tool made available to the model during an agent step, described the
+/// way Anthropic's `tools` field expects (`name`/`description`/`input_schema`).
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: serde_json::Value,
+}
+
+/// A single content block exchanged during an agent step, shaped to match
+/// Anthropic's content blocks closely enough to round-trip a `tool_use` →
+/// `tool_result` exchange without any translation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentBlock {
+    Text { text: String },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+}
+
+/// One user or assistant turn of an agentic tool-calling conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTurn {
+    pub role: String,
+    pub content: Vec<AgentBlock>,
+}
+
+impl AgentTurn {
+    pub fn user_text(text: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: vec![AgentBlock::Text { text: text.into() }],
+        }
+    }
+
+    pub fn assistant_tool_use(blocks: Vec<AgentBlock>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: blocks,
+        }
+    }
+
+    pub fn tool_results(results: Vec<AgentBlock>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: results,
+        }
+    }
+
+    /// Concatenates this turn's text blocks, ignoring any tool-related ones.
+    pub(crate) fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                AgentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+/// The result of one `complete_agent_step` call.
+#[derive(Debug, Clone)]
+pub enum AgentStep {
+    /// The model's final plain-text reply; the conversation is done.
+    Text(String),
+    /// Tool calls the model wants run before it can continue.
+    ToolUse(Vec<AgentBlock>),
+}
+
+/// Serde-tagged configuration for the supported providers. Each variant
+/// carries everything its provider needs to make a request. `from_env`
+/// builds it from environment variables; nothing in this crate reads it from
+/// a config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ProviderConfig {
+    Anthropic {
+        api_base: String,
+        model: String,
+        api_key: String,
+    },
+    OpenAI {
+        api_base: String,
+        model: String,
+        api_key: String,
+    },
+    Ollama {
+        api_base: String,
+        model: String,
+        #[serde(default)]
+        api_key: String,
+    },
+}
+
+impl ProviderConfig {
+    /// Builds the config from environment variables, preferring Anthropic for
+    /// backward compatibility with the pre-provider-abstraction setup.
+    pub fn from_env() -> Option<Self> {
+        if let Ok(api_key) = std::env::var("ANTHROPIC_API_KEY") {
+            return Some(ProviderConfig::Anthropic {
+                api_base: std::env::var("ANTHROPIC_API_BASE")
+                    .unwrap_or_else(|_| "https://api.anthropic.com".to_string()),
+                model: std::env::var("ANTHROPIC_MODEL")
+                    .unwrap_or_else(|_| "claude-3-5-sonnet-20241022".to_string()),
+                api_key,
+            });
+        }
+
+        if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+            return Some(ProviderConfig::OpenAI {
+                api_base: std::env::var("OPENAI_API_BASE")
+                    .unwrap_or_else(|_| "https://api.openai.com".to_string()),
+                model: std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+                api_key,
+            });
+        }
+
+        if let Ok(api_base) = std::env::var("OLLAMA_API_BASE") {
+            return Some(ProviderConfig::Ollama {
+                api_base,
+                model: std::env::var("OLLAMA_MODEL").unwrap_or_else(|_| "llama3".to_string()),
+                api_key: String::new(),
+            });
+        }
+
+        None
+    }
+
+    pub fn build(self) -> Box<dyn LlmProvider> {
+        match self {
+            ProviderConfig::Anthropic {
+                api_base,
+                model,
+                api_key,
+            } => Box::new(AnthropicProvider::new(api_base, model, api_key)),
+            ProviderConfig::OpenAI {
+                api_base,
+                model,
+                api_key,
+            } => Box::new(OpenAiProvider::new(api_base, model, api_key)),
+            ProviderConfig::Ollama {
+                api_base,
+                model,
+                api_key,
+            } => Box::new(OllamaProvider::new(api_base, model, api_key)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicContent {
+    text: String,
+}
+
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    api_base: String,
+    model: String,
+    api_key: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_base: String, model: String, api_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_base,
+            model,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn complete(&self, messages: &[ChatMessage], max_tokens: u32) -> Result<String> {
+        let request = AnthropicRequest {
+            model: self.model.clone(),
+            max_tokens,
+            messages: messages.to_vec(),
+            stream: false,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/v1/messages", self.api_base))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to send request to Anthropic API")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Anthropic API error ({}): {}", status, error_text);
+        }
+
+        let parsed: AnthropicResponse = response
+            .json()
+            .await
+            .context("Failed to parse Anthropic API response")?;
+
+        Ok(parsed
+            .content
+            .first()
+            .map(|c| c.text.clone())
+            .unwrap_or_default())
+    }
+
+    async fn complete_stream(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: u32,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let request = AnthropicRequest {
+            model: self.model.clone(),
+            max_tokens,
+            messages: messages.to_vec(),
+            stream: true,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/v1/messages", self.api_base))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to send request to Anthropic API")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Anthropic API error ({}): {}", status, error_text);
+        }
+
+        Ok(parse_anthropic_sse(response.bytes_stream()).boxed())
+    }
+
+    async fn complete_tool(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: u32,
+        tool_name: &str,
+        tool_description: &str,
+        tool_schema: serde_json::Value,
+    ) -> Result<Option<serde_json::Value>> {
+        let request = AnthropicToolRequest {
+            model: self.model.clone(),
+            max_tokens,
+            messages: messages.to_vec(),
+            tools: vec![AnthropicTool {
+                name: tool_name,
+                description: tool_description,
+                input_schema: tool_schema,
+            }],
+            tool_choice: AnthropicToolChoice {
+                kind: "tool",
+                name: tool_name,
+            },
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/v1/messages", self.api_base))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to send tool-call request to Anthropic API")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Anthropic API error ({}): {}", status, error_text);
+        }
+
+        let parsed: AnthropicToolResponse = response
+            .json()
+            .await
+            .context("Failed to parse Anthropic tool-call response")?;
+
+        let input = parsed.content.into_iter().find_map(|block| {
+            if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                block.get("input").cloned()
+            } else {
+                None
+            }
+        });
+
+        Ok(input)
+    }
+
+    async fn complete_agent_step(
+        &self,
+        history: &[AgentTurn],
+        tools: &[ToolDefinition],
+    ) -> Result<AgentStep> {
+        let request = AnthropicAgentRequest {
+            model: self.model.clone(),
+            max_tokens: 2048,
+            messages: history.to_vec(),
+            tools: tools
+                .iter()
+                .map(|tool| AnthropicAgentTool {
+                    name: tool.name,
+                    description: tool.description,
+                    input_schema: tool.input_schema.clone(),
+                })
+                .collect(),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/v1/messages", self.api_base))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to send agent-step request to Anthropic API")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Anthropic API error ({}): {}", status, error_text);
+        }
+
+        let parsed: AnthropicAgentResponse = response
+            .json()
+            .await
+            .context("Failed to parse Anthropic API agent-step response")?;
+
+        let tool_uses: Vec<AgentBlock> = parsed
+            .content
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+            .filter_map(|block| serde_json::from_value(block.clone()).ok())
+            .collect();
+
+        if !tool_uses.is_empty() {
+            return Ok(AgentStep::ToolUse(tool_uses));
+        }
+
+        let text = parsed
+            .content
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("");
+
+        Ok(AgentStep::Text(text))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicAgentRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<AgentTurn>,
+    tools: Vec<AnthropicAgentTool>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicAgentTool {
+    name: &'static str,
+    description: &'static str,
+    input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicAgentResponse {
+    content: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicToolRequest<'a> {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<ChatMessage>,
+    tools: Vec<AnthropicTool<'a>>,
+    tool_choice: AnthropicToolChoice<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicTool<'a> {
+    name: &'a str,
+    description: &'a str,
+    input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicToolChoice<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicToolResponse {
+    content: Vec<serde_json::Value>,
+}
+
+/// Parses an Anthropic `text/event-stream` body into a stream of incremental
+/// text deltas. Each event looks like `data: {json}`; we care about
+/// `content_block_delta` events (whose `delta.text` is the next chunk) and
+/// stop once a `message_stop` event arrives.
+pub(crate) fn parse_anthropic_sse(
+    bytes: impl futures::Stream<Item = reqwest::Result<bytes::Bytes>> + Send + 'static,
+) -> impl futures::Stream<Item = Result<String>> + Send + 'static {
+    stream::unfold(
+        (bytes.boxed(), String::new(), false),
+        |(mut bytes, mut buf, done)| async move {
+            if done {
+                return None;
+            }
+
+            loop {
+                if let Some(pos) = buf.find('\n') {
+                    let line = buf[..pos].trim_end_matches('\r').to_string();
+                    buf.drain(..=pos);
+
+                    let Some(data) = line.strip_prefix("data: ") else {
+                        continue;
+                    };
+
+                    let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+                        continue;
+                    };
+
+                    match event.get("type").and_then(|t| t.as_str()) {
+                        Some("message_stop") => {
+                            return Some((Ok(String::new()), (bytes, buf, true)));
+                        }
+                        Some("content_block_delta") => {
+                            if let Some(text) = event
+                                .pointer("/delta/text")
+                                .and_then(|t| t.as_str())
+                            {
+                                return Some((Ok(text.to_string()), (bytes, buf, false)));
+                            }
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                match bytes.next().await {
+                    Some(Ok(chunk)) => {
+                        buf.push_str(&String::from_utf8_lossy(&chunk));
+                    }
+                    Some(Err(e)) => {
+                        return Some((Err(anyhow::Error::from(e)), (bytes, buf, true)));
+                    }
+                    None => return None,
+                }
+            }
+        },
+    )
+    .filter(|r| {
+        let keep = !matches!(r, Ok(s) if s.is_empty());
+        async move { keep }
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAiRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    choices: Vec<OpenAiChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChoice {
+    message: ChatMessage,
+}
+
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    api_base: String,
+    model: String,
+    api_key: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_base: String, model: String, api_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_base,
+            model,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn complete(&self, messages: &[ChatMessage], max_tokens: u32) -> Result<String> {
+        let request = OpenAiRequest {
+            model: self.model.clone(),
+            max_tokens,
+            messages: messages.to_vec(),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/v1/chat/completions", self.api_base))
+            .bearer_auth(&self.api_key)
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to send request to OpenAI-compatible API")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("OpenAI-compatible API error ({}): {}", status, error_text);
+        }
+
+        let parsed: OpenAiResponse = response
+            .json()
+            .await
+            .context("Failed to parse OpenAI-compatible API response")?;
+
+        Ok(parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message.content)
+            .unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaResponse {
+    message: ChatMessage,
+}
+
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    api_base: String,
+    model: String,
+    #[allow(dead_code)]
+    api_key: String,
+}
+
+impl OllamaProvider {
+    pub fn new(api_base: String, model: String, api_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_base,
+            model,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    async fn complete(&self, messages: &[ChatMessage], _max_tokens: u32) -> Result<String> {
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            messages: messages.to_vec(),
+            stream: false,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.api_base))
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to send request to Ollama")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Ollama error ({}): {}", status, error_text);
+        }
+
+        let parsed: OllamaResponse = response
+            .json()
+            .await
+            .context("Failed to parse Ollama response")?;
+
+        Ok(parsed.message.content)
+    }
+}
+
+/// Built-in provider used when no real backend is configured. It replaces the
+/// old "no API key" branches with simple rule-based heuristics so the rest of
+/// the code never has to special-case the absence of a provider.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullProvider;
+
+impl NullProvider {
+    fn extract_code(content: &str) -> &str {
+        content
+            .split("```python")
+            .nth(1)
+            .and_then(|rest| rest.split("```").next())
+            .unwrap_or(content)
+            .trim()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for NullProvider {
+    async fn complete(&self, messages: &[ChatMessage], _max_tokens: u32) -> Result<String> {
+        let last_content = messages
+            .last()
+            .map(|m| m.content.as_str())
+            .unwrap_or_default();
+        let code = Self::extract_code(last_content);
+
+        let mut suggestions = Vec::new();
+        let mut quality_score = 0;
+
+        if code.len() < 10 {
+            suggestions.push(
+                "- Your code seems quite short. Make sure you've completed all the requirements."
+                    .to_string(),
+            );
+        } else {
+            quality_score += 1;
+        }
+
+        if code.contains("def ") {
+            quality_score += 1;
+        }
+
+        if code.lines().count() > 1 {
+            quality_score += 1;
+        }
+
+        let quality_label = match quality_score {
+            3 => "Excellent",
+            2 => "Good",
+            1 => "Needs Improvement",
+            _ => "Poor",
+        };
+
+        let mut feedback = format!(
+            "Code review: Your code has been submitted. Quality: {}",
+            quality_label
+        );
+        if !suggestions.is_empty() {
+            feedback.push('\n');
+            feedback.push_str(&suggestions.join("\n"));
+        }
+
+        Ok(feedback)
+    }
+}
@@ -0,0 +1,527 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::models::{Challenge, Flashcard, Message, MessageRole, SessionState, TeachingPhase};
+
+/// Persists tutoring sessions, conversation history, code submissions, and
+/// exercise progress, so a server restart doesn't wipe out a learner's
+/// place. `InteractiveTutor` talks to this trait rather than `SqliteStore`
+/// directly, so tests and local/offline runs can swap in `InMemoryStore`
+/// without touching the teaching logic.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Upserts a session's lesson/phase/challenge metadata. Conversation
+    /// history, submissions, and progress are persisted separately via their
+    /// own append-only methods.
+    async fn save_session(&self, session: &SessionState) -> Result<()>;
+
+    async fn append_message(&self, session_id: Uuid, message: &Message) -> Result<()>;
+
+    async fn record_submission(
+        &self,
+        session_id: Uuid,
+        lesson_id: &str,
+        exercise_id: Option<&str>,
+        code: &str,
+        success: bool,
+        timestamp: u64,
+    ) -> Result<()>;
+
+    async fn mark_exercise_complete(
+        &self,
+        session_id: Uuid,
+        lesson_id: &str,
+        exercise_id: &str,
+        timestamp: u64,
+    ) -> Result<()>;
+
+    /// Rehydrates a full `SessionState` from its session metadata,
+    /// conversation history, and completed exercises. Returns `None` if no
+    /// session with this id has ever been persisted.
+    async fn load_session(&self, session_id: Uuid) -> Result<Option<SessionState>>;
+
+    /// Upserts a flashcard (by id), for both creating a new "Learning
+    /// Moment" and rescheduling one after a review.
+    async fn save_flashcard(&self, session_id: Uuid, card: &Flashcard) -> Result<()>;
+
+    /// Returns the session's flashcards due for review (`due_at <= now`),
+    /// oldest first.
+    async fn due_flashcards(&self, session_id: Uuid, now: u64) -> Result<Vec<Flashcard>>;
+}
+
+/// SQLite-backed `SessionStore`. Tables mirror the shape of `SessionState`:
+///
+/// - `sessions`   — one row per session (current lesson, teaching phase, active challenge)
+/// - `messages`   — the conversation history, append-only
+/// - `submissions` — every code submission a student ran, with pass/fail
+/// - `progress`   — lessons/exercises a student has mastered
+/// - `flashcards` — "Learning Moments" scheduled for spaced-repetition review
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect(database_url)
+            .await
+            .with_context(|| format!("Failed to connect to {}", database_url))?;
+
+        let store = Self { pool };
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    async fn migrate(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                session_id TEXT PRIMARY KEY,
+                current_lesson_id TEXT NOT NULL,
+                teaching_phase TEXT NOT NULL,
+                current_challenge TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create sessions table")?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                timestamp INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create messages table")?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS submissions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                lesson_id TEXT NOT NULL,
+                exercise_id TEXT,
+                code TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create submissions table")?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS progress (
+                session_id TEXT NOT NULL,
+                lesson_id TEXT NOT NULL,
+                exercise_id TEXT NOT NULL,
+                mastered_at INTEGER NOT NULL,
+                PRIMARY KEY (session_id, lesson_id, exercise_id)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create progress table")?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS flashcards (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                concept TEXT NOT NULL,
+                front TEXT NOT NULL,
+                back TEXT NOT NULL,
+                due_at INTEGER NOT NULL,
+                interval_days INTEGER NOT NULL,
+                ease REAL NOT NULL,
+                repetitions INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create flashcards table")?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SessionStore for SqliteStore {
+    async fn save_session(&self, session: &SessionState) -> Result<()> {
+        let phase = serde_json::to_string(&session.teaching_phase)?;
+        let challenge = session
+            .current_challenge
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (session_id, current_lesson_id, teaching_phase, current_challenge)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(session_id) DO UPDATE SET
+                current_lesson_id = excluded.current_lesson_id,
+                teaching_phase = excluded.teaching_phase,
+                current_challenge = excluded.current_challenge
+            "#,
+        )
+        .bind(session.session_id.to_string())
+        .bind(&session.current_lesson_id)
+        .bind(phase)
+        .bind(challenge)
+        .execute(&self.pool)
+        .await
+        .context("Failed to save session")?;
+
+        Ok(())
+    }
+
+    async fn append_message(&self, session_id: Uuid, message: &Message) -> Result<()> {
+        let role = serde_json::to_string(&message.role)?;
+
+        sqlx::query(
+            "INSERT INTO messages (session_id, role, content, timestamp) VALUES (?, ?, ?, ?)",
+        )
+        .bind(session_id.to_string())
+        .bind(role)
+        .bind(&message.content)
+        .bind(message.timestamp as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to append message")?;
+
+        Ok(())
+    }
+
+    async fn record_submission(
+        &self,
+        session_id: Uuid,
+        lesson_id: &str,
+        exercise_id: Option<&str>,
+        code: &str,
+        success: bool,
+        timestamp: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO submissions (session_id, lesson_id, exercise_id, code, success, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(session_id.to_string())
+        .bind(lesson_id)
+        .bind(exercise_id)
+        .bind(code)
+        .bind(success)
+        .bind(timestamp as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record submission")?;
+
+        Ok(())
+    }
+
+    async fn mark_exercise_complete(
+        &self,
+        session_id: Uuid,
+        lesson_id: &str,
+        exercise_id: &str,
+        timestamp: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO progress (session_id, lesson_id, exercise_id, mastered_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(session_id.to_string())
+        .bind(lesson_id)
+        .bind(exercise_id)
+        .bind(timestamp as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark exercise complete")?;
+
+        Ok(())
+    }
+
+    async fn load_session(&self, session_id: Uuid) -> Result<Option<SessionState>> {
+        let session_row = sqlx::query(
+            "SELECT current_lesson_id, teaching_phase, current_challenge FROM sessions WHERE session_id = ?",
+        )
+        .bind(session_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load session")?;
+
+        let Some(row) = session_row else {
+            return Ok(None);
+        };
+
+        let current_lesson_id: String = row.get("current_lesson_id");
+        let teaching_phase: TeachingPhase = serde_json::from_str(row.get("teaching_phase"))?;
+        let current_challenge: Option<Challenge> = row
+            .get::<Option<String>, _>("current_challenge")
+            .map(|c| serde_json::from_str(&c))
+            .transpose()?;
+
+        let message_rows = sqlx::query(
+            "SELECT role, content, timestamp FROM messages WHERE session_id = ? ORDER BY id ASC",
+        )
+        .bind(session_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load conversation history")?;
+
+        let mut conversation_history = Vec::with_capacity(message_rows.len());
+        for row in message_rows {
+            let role: MessageRole = serde_json::from_str(row.get("role"))?;
+            conversation_history.push(Message {
+                role,
+                content: row.get("content"),
+                timestamp: row.get::<i64, _>("timestamp") as u64,
+            });
+        }
+
+        let completed_rows = sqlx::query(
+            "SELECT DISTINCT exercise_id FROM progress WHERE session_id = ? ORDER BY mastered_at ASC",
+        )
+        .bind(session_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load progress")?;
+        let completed_exercises = completed_rows
+            .into_iter()
+            .map(|row| row.get("exercise_id"))
+            .collect();
+
+        let code_rows = sqlx::query(
+            "SELECT code FROM submissions WHERE session_id = ? ORDER BY id ASC",
+        )
+        .bind(session_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load code history")?;
+        let code_history = code_rows.into_iter().map(|row| row.get("code")).collect();
+
+        Ok(Some(SessionState {
+            session_id,
+            current_lesson_id,
+            teaching_phase,
+            conversation_history,
+            current_challenge,
+            completed_exercises,
+            code_history,
+        }))
+    }
+
+    async fn save_flashcard(&self, session_id: Uuid, card: &Flashcard) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO flashcards (id, session_id, concept, front, back, due_at, interval_days, ease, repetitions)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                due_at = excluded.due_at,
+                interval_days = excluded.interval_days,
+                ease = excluded.ease,
+                repetitions = excluded.repetitions
+            "#,
+        )
+        .bind(card.id.to_string())
+        .bind(session_id.to_string())
+        .bind(&card.concept)
+        .bind(&card.front)
+        .bind(&card.back)
+        .bind(card.due_at as i64)
+        .bind(card.interval_days as i64)
+        .bind(card.ease)
+        .bind(card.repetitions as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to save flashcard")?;
+
+        Ok(())
+    }
+
+    async fn due_flashcards(&self, session_id: Uuid, now: u64) -> Result<Vec<Flashcard>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, concept, front, back, due_at, interval_days, ease, repetitions
+            FROM flashcards
+            WHERE session_id = ? AND due_at <= ?
+            ORDER BY due_at ASC
+            "#,
+        )
+        .bind(session_id.to_string())
+        .bind(now as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load due flashcards")?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Flashcard {
+                    id: Uuid::parse_str(row.get("id")).context("Invalid flashcard id")?,
+                    concept: row.get("concept"),
+                    front: row.get("front"),
+                    back: row.get("back"),
+                    due_at: row.get::<i64, _>("due_at") as u64,
+                    interval_days: row.get::<i64, _>("interval_days") as u32,
+                    ease: row.get("ease"),
+                    repetitions: row.get::<i64, _>("repetitions") as u32,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Non-persistent `SessionStore` for tests and local/offline runs: holds the
+/// same shape of data `SqliteStore` would write to disk, just in a
+/// process-local map that's gone once the server exits.
+#[derive(Default)]
+pub struct InMemoryStore {
+    sessions: tokio::sync::RwLock<std::collections::HashMap<Uuid, SessionRecord>>,
+}
+
+#[derive(Clone)]
+struct SessionRecord {
+    current_lesson_id: String,
+    teaching_phase: TeachingPhase,
+    current_challenge: Option<Challenge>,
+    conversation_history: Vec<Message>,
+    completed_exercises: Vec<String>,
+    code_history: Vec<String>,
+    flashcards: Vec<Flashcard>,
+}
+
+impl Default for SessionRecord {
+    fn default() -> Self {
+        Self {
+            current_lesson_id: String::new(),
+            teaching_phase: TeachingPhase::Introduction,
+            current_challenge: None,
+            conversation_history: Vec::new(),
+            completed_exercises: Vec::new(),
+            code_history: Vec::new(),
+            flashcards: Vec::new(),
+        }
+    }
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemoryStore {
+    async fn save_session(&self, session: &SessionState) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        let record = sessions.entry(session.session_id).or_default();
+        record.current_lesson_id = session.current_lesson_id.clone();
+        record.teaching_phase = session.teaching_phase.clone();
+        record.current_challenge = session.current_challenge.clone();
+        Ok(())
+    }
+
+    async fn append_message(&self, session_id: Uuid, message: &Message) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        sessions
+            .entry(session_id)
+            .or_default()
+            .conversation_history
+            .push(message.clone());
+        Ok(())
+    }
+
+    async fn record_submission(
+        &self,
+        session_id: Uuid,
+        _lesson_id: &str,
+        _exercise_id: Option<&str>,
+        code: &str,
+        _success: bool,
+        _timestamp: u64,
+    ) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        sessions
+            .entry(session_id)
+            .or_default()
+            .code_history
+            .push(code.to_string());
+        Ok(())
+    }
+
+    async fn mark_exercise_complete(
+        &self,
+        session_id: Uuid,
+        _lesson_id: &str,
+        exercise_id: &str,
+        _timestamp: u64,
+    ) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        sessions
+            .entry(session_id)
+            .or_default()
+            .completed_exercises
+            .push(exercise_id.to_string());
+        Ok(())
+    }
+
+    async fn load_session(&self, session_id: Uuid) -> Result<Option<SessionState>> {
+        let sessions = self.sessions.read().await;
+        Ok(sessions.get(&session_id).map(|record| SessionState {
+            session_id,
+            current_lesson_id: record.current_lesson_id.clone(),
+            teaching_phase: record.teaching_phase.clone(),
+            conversation_history: record.conversation_history.clone(),
+            current_challenge: record.current_challenge.clone(),
+            completed_exercises: record.completed_exercises.clone(),
+            code_history: record.code_history.clone(),
+        }))
+    }
+
+    async fn save_flashcard(&self, session_id: Uuid, card: &Flashcard) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        let flashcards = &mut sessions.entry(session_id).or_default().flashcards;
+        match flashcards.iter_mut().find(|c| c.id == card.id) {
+            Some(existing) => *existing = card.clone(),
+            None => flashcards.push(card.clone()),
+        }
+        Ok(())
+    }
+
+    async fn due_flashcards(&self, session_id: Uuid, now: u64) -> Result<Vec<Flashcard>> {
+        let sessions = self.sessions.read().await;
+        let mut due: Vec<Flashcard> = sessions
+            .get(&session_id)
+            .map(|record| {
+                record
+                    .flashcards
+                    .iter()
+                    .filter(|c| c.due_at <= now)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        due.sort_by_key(|c| c.due_at);
+        Ok(due)
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::Arc;
+
+use crate::providers::{AgentStep, AgentTurn, ChatMessage, LlmProvider, ToolDefinition};
+
+/// A backend capable of turning a single prompt into a completion, used by
+/// the interactive tutor for its teaching/hinting/evaluation calls. Kept
+/// separate from `crate::providers::LlmProvider` (which speaks in terms of a
+/// `ChatMessage` conversation, for the AI code reviewer) since the tutor only
+/// ever sends one freeform prompt per turn; `LlmCompletionProvider` adapts a
+/// real `LlmProvider` to this shape so both callers share the same HTTP
+/// client stack and env-var config loader instead of each talking to
+/// Anthropic/OpenAI directly.
+#[async_trait]
+pub trait CompletionProvider: Send + Sync {
+    async fn complete(&self, prompt: &str) -> Result<String>;
+
+    /// Streams the completion as incremental text deltas. Providers that
+    /// don't support native streaming can fall back to this default, which
+    /// just awaits the full completion and yields it as a single chunk.
+    async fn complete_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        let text = self.complete(prompt).await?;
+        Ok(stream::once(async move { Ok(text) }).boxed())
+    }
+
+    /// Advances one step of an agentic, tool-calling conversation: `history`
+    /// is every turn exchanged so far (starting with a single user turn),
+    /// `tools` describes what the model may invoke. Returns the model's
+    /// plain-text reply once it's done, or the tool calls it wants run next -
+    /// callers should run each one, append its `AgentTurn::tool_results` to
+    /// `history`, and call this again until a `Text` step comes back.
+    ///
+    /// Providers that don't support tool calling can rely on this default,
+    /// which just completes the last user turn's text as a plain prompt and
+    /// never asks for a tool call.
+    async fn complete_agent_step(
+        &self,
+        history: &[AgentTurn],
+        _tools: &[ToolDefinition],
+    ) -> Result<AgentStep> {
+        let prompt = history
+            .iter()
+            .rev()
+            .find(|turn| turn.role == "user")
+            .map(|turn| turn.text())
+            .unwrap_or_default();
+
+        Ok(AgentStep::Text(self.complete(&prompt).await?))
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider>;
+}
+
+impl Clone for Box<dyn CompletionProvider> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Adapts a `crate::providers::LlmProvider` to the tutor's single-prompt
+/// `CompletionProvider` shape, so the tutor can be pointed at Claude, OpenAI,
+/// or a local OpenAI-compatible endpoint via `crate::providers::ProviderConfig`
+/// (the same backend selection the AI code reviewer uses) without the tutor
+/// needing its own copy of the HTTP client code.
+#[derive(Clone)]
+pub struct LlmCompletionProvider {
+    inner: Arc<dyn LlmProvider>,
+}
+
+impl LlmCompletionProvider {
+    pub fn new(inner: Box<dyn LlmProvider>) -> Self {
+        Self {
+            inner: Arc::from(inner),
+        }
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for LlmCompletionProvider {
+    async fn complete(&self, prompt: &str) -> Result<String> {
+        self.inner
+            .complete(&[ChatMessage::user(prompt)], 2048)
+            .await
+    }
+
+    async fn complete_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        self.inner
+            .complete_stream(&[ChatMessage::user(prompt)], 2048)
+            .await
+    }
+
+    async fn complete_agent_step(
+        &self,
+        history: &[AgentTurn],
+        tools: &[ToolDefinition],
+    ) -> Result<AgentStep> {
+        self.inner.complete_agent_step(history, tools).await
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new(self.clone())
+    }
+}
+
+/// Replaces the old `api_key.is_none()` branches scattered through
+/// `InteractiveTutor`: when no real backend is configured, this provider
+/// stands in for it. Since `CompletionProvider` only sees the rendered
+/// prompt (not which teaching step produced it), it recognizes the step from
+/// a short marker phrase every prompt-builder includes and still pulls out
+/// lesson-specific details embedded in the prompt where it can, matching the
+/// canned replies this repo used before the provider abstraction existed
+/// (except the introduction, which names only the lesson title).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullCompletionProvider;
+
+impl NullCompletionProvider {
+    fn field<'a>(prompt: &'a str, marker: &str) -> Option<&'a str> {
+        prompt
+            .lines()
+            .find_map(|line| line.strip_prefix(marker))
+            .map(|s| s.trim())
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for NullCompletionProvider {
+    async fn complete(&self, prompt: &str) -> Result<String> {
+        let lesson_title = Self::field(prompt, "Lesson: ").unwrap_or("this lesson");
+
+        let response = if prompt.contains("Provide a warm, encouraging introduction that:") {
+            format!(
+                "Welcome to {}! In this lesson, you'll build on what you already know. Are you ready to get started?",
+                lesson_title
+            )
+        } else if prompt.contains("Continue teaching about these concepts.") {
+            format!(
+                "Let's learn about {}. Try writing some code to practice the concepts.",
+                lesson_title
+            )
+        } else if prompt.contains("Provide guidance using the Socratic method:") {
+            "Think about the problem step by step. What do you need to do first?".to_string()
+        } else if prompt.contains("Provide a helpful hint that:") {
+            "Try breaking down the problem into smaller steps. What's the first thing you need to do?".to_string()
+        } else if prompt.contains("Provide a step-by-step walkthrough that:") {
+            "Let me walk you through this step by step. Since I've helped you through this, I'll give you a new challenge to demonstrate your understanding.".to_string()
+        } else if prompt.contains("Evaluate if the student has demonstrated understanding of the lesson objectives:")
+        {
+            let output = prompt
+                .split_once("\nOutput: ")
+                .and_then(|(_, rest)| rest.split_once("\n\nEvaluate if the student"))
+                .map(|(output, _)| output)
+                .unwrap_or_default();
+            format!(
+                "MASTERY: YES\nFEEDBACK:\nGreat job! Your code works! Output:\n{}\n\nYou've demonstrated understanding of this lesson!",
+                output
+            )
+        } else if prompt.contains("Now generate a NEW, DIFFERENT challenge that:") {
+            "Great! Now try creating a similar solution but for a different scenario. You've got this!".to_string()
+        } else {
+            "Let's keep going - try the next step!".to_string()
+        };
+
+        Ok(response)
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new(*self)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::models::Flashcard;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Builds a brand-new flashcard for a "Learning Moment", due for its first
+/// review immediately.
+pub(super) fn new_flashcard(concept: String, front: String, back: String, now: u64) -> Flashcard {
+    Flashcard {
+        id: uuid::Uuid::new_v4(),
+        concept,
+        front,
+        back,
+        due_at: now,
+        interval_days: 1,
+        ease: 2.5,
+        repetitions: 0,
+    }
+}
+
+/// Reschedules a flashcard after a review using the SM-2 algorithm: a grade
+/// below 3 means the card was forgotten and starts over, otherwise the
+/// interval grows to 1 day (first success), 6 days (second), or
+/// `round(prev_interval * ease)` (third and later).
+pub(super) fn schedule_review(card: &mut Flashcard, grade: u8, now: u64) {
+    let prev_interval = card.interval_days;
+
+    if grade < 3 {
+        card.repetitions = 0;
+        card.interval_days = 1;
+    } else {
+        card.repetitions += 1;
+        card.interval_days = match card.repetitions {
+            1 => 1,
+            2 => 6,
+            _ => (prev_interval as f64 * card.ease).round() as u32,
+        };
+    }
+
+    let grade = grade as f64;
+    card.ease = (card.ease + (0.1 - (5.0 - grade) * (0.08 + (5.0 - grade) * 0.02))).max(1.3);
+
+    card.due_at = now + card.interval_days as u64 * SECONDS_PER_DAY;
+}
@@ -1,5 +1,7 @@
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use futures::stream::{BoxStream, StreamExt};
+use minijinja::context;
+use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -8,61 +10,169 @@ use uuid::Uuid;
 use crate::executor::CodeExecutor;
 use crate::lessons::LessonManager;
 use crate::models::*;
+use crate::store::SessionStore;
+
+mod completion_provider;
+pub use completion_provider::{CompletionProvider, LlmCompletionProvider, NullCompletionProvider};
+use crate::providers::{AgentBlock, AgentStep, AgentTurn, ToolDefinition};
+
+mod flashcards;
+
+mod prompts;
+use prompts::PromptEngine;
+
+#[cfg(feature = "voice")]
+pub mod voice;
+#[cfg(feature = "voice")]
+use voice::VoiceConfig;
 
 pub struct InteractiveTutor {
-    api_key: Option<String>,
-    client: reqwest::Client,
+    provider: Arc<dyn CompletionProvider>,
     sessions: Arc<RwLock<HashMap<Uuid, SessionState>>>,
     lesson_manager: Arc<LessonManager>,
     code_executor: Arc<CodeExecutor>,
+    store: Arc<dyn SessionStore>,
+    prompts: PromptEngine,
+    #[cfg(feature = "voice")]
+    voice: Option<VoiceConfig>,
 }
 
-#[derive(Debug, Serialize)]
-struct ClaudeRequest {
-    model: String,
-    max_tokens: u32,
-    messages: Vec<ClaudeMessage>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ClaudeMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ClaudeResponse {
-    content: Vec<ClaudeContent>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ClaudeContent {
-    text: String,
+/// One event emitted while streaming a tutor interaction: either the next
+/// chunk of message text, or the fully assembled `TutorResponse` once the
+/// reply (and any resulting phase transition) is complete.
+pub enum TutorStreamEvent {
+    Delta(String),
+    Done(Box<TutorResponse>),
 }
 
 impl InteractiveTutor {
     pub fn new(
-        api_key: Option<String>,
+        provider: Box<dyn CompletionProvider>,
         lesson_manager: Arc<LessonManager>,
         code_executor: Arc<CodeExecutor>,
+        store: Arc<dyn SessionStore>,
+        #[cfg(feature = "voice")] voice: Option<VoiceConfig>,
     ) -> Self {
+        let mut prompts = PromptEngine::new();
+        if let Ok(dir) = std::env::var("TUTOR_PROMPTS_DIR") {
+            if let Err(e) = prompts.load_overrides(&dir) {
+                tracing::warn!("Failed to load prompt overrides from {}: {}", dir, e);
+            }
+        }
+
         Self {
-            api_key,
-            client: reqwest::Client::new(),
+            provider: Arc::from(provider),
             sessions: Arc::new(RwLock::new(HashMap::new())),
             lesson_manager,
             code_executor,
+            store,
+            prompts,
+            #[cfg(feature = "voice")]
+            voice,
+        }
+    }
+
+    /// Loads a session by id, checking the in-memory cache first and falling
+    /// back to the persisted copy (rehydrating the cache) so a resumed
+    /// session doesn't have to hit the database on every turn.
+    async fn get_session(&self, session_id: Uuid) -> Result<SessionState> {
+        if let Some(session) = self.sessions.read().await.get(&session_id) {
+            return Ok(session.clone());
+        }
+
+        let session = self
+            .store
+            .load_session(session_id)
+            .await?
+            .context("Session not found")?;
+
+        self.sessions
+            .write()
+            .await
+            .insert(session_id, session.clone());
+
+        Ok(session)
+    }
+
+    /// Resumes a previously started session, rehydrating it from storage if
+    /// it isn't already cached in memory. Returns `None` if the session has
+    /// never been started.
+    pub async fn resume_session(&self, session_id: Uuid) -> Result<Option<SessionState>> {
+        match self.get_session(session_id).await {
+            Ok(session) => Ok(Some(session)),
+            Err(_) => Ok(None),
         }
     }
 
     pub async fn handle_request(&self, request: TutorRequest) -> Result<TutorResponse> {
-        match request.request_type {
+        let response = match request.request_type {
             TutorRequestType::StartLesson => self.start_lesson(request).await,
             TutorRequestType::SendMessage => self.handle_message(request).await,
             TutorRequestType::SubmitCode => self.handle_code_submission(request).await,
             TutorRequestType::RequestHint => self.provide_hint(request).await,
             TutorRequestType::RequestWalkthrough => self.provide_walkthrough(request).await,
+            TutorRequestType::ReviewFlashcards => self.review_flashcards(request).await,
+        }?;
+
+        #[cfg(feature = "voice")]
+        let response = self.narrate(response).await;
+
+        Ok(response)
+    }
+
+    /// Transcribes streamed microphone audio and feeds the transcript into
+    /// the existing `SendMessage` path, so a spoken turn is handled the same
+    /// way a typed one is. Requires the `voice` feature and a configured
+    /// transcriber.
+    #[cfg(feature = "voice")]
+    pub async fn handle_voice_message(
+        &self,
+        session_id: Uuid,
+        audio: BoxStream<'static, Result<bytes::Bytes>>,
+    ) -> Result<TutorResponse> {
+        let voice = self
+            .voice
+            .as_ref()
+            .context("Voice subsystem is not configured")?;
+
+        let transcript = voice.transcriber.transcribe(audio).await?;
+        if transcript.trim().is_empty() {
+            anyhow::bail!("Transcription didn't recognize any speech in the submitted audio");
         }
+
+        let session = self.get_session(session_id).await?;
+
+        self.handle_request(TutorRequest {
+            session_id,
+            lesson_id: session.current_lesson_id.clone(),
+            message: Some(transcript),
+            code: None,
+            exercise_id: None,
+            flashcard_review: None,
+            request_type: TutorRequestType::SendMessage,
+        })
+        .await
+    }
+
+    /// Synthesizes `response.message` to speech via the configured narrator,
+    /// attaching the audio and its speech marks. Leaves the response as
+    /// plain text (rather than failing the whole request) if narration
+    /// fails or no narrator is configured.
+    #[cfg(feature = "voice")]
+    async fn narrate(&self, mut response: TutorResponse) -> TutorResponse {
+        let Some(voice) = &self.voice else {
+            return response;
+        };
+
+        match voice.narrator.synthesize(&response.message).await {
+            Ok((audio, speech_marks)) => {
+                response.audio = Some(audio);
+                response.speech_marks = speech_marks;
+            }
+            Err(e) => tracing::warn!("Failed to synthesize tutor reply: {}", e),
+        }
+
+        response
     }
 
     async fn start_lesson(&self, request: TutorRequest) -> Result<TutorResponse> {
@@ -71,8 +181,6 @@ impl InteractiveTutor {
             .get_lesson(&request.lesson_id)
             .context("Lesson not found")?;
 
-        let mut sessions = self.sessions.write().await;
-
         let session = SessionState {
             session_id: request.session_id,
             current_lesson_id: request.lesson_id.clone(),
@@ -83,8 +191,8 @@ impl InteractiveTutor {
             code_history: Vec::new(),
         };
 
-        sessions.insert(request.session_id, session);
-        drop(sessions);
+        self.store.save_session(&session).await?;
+        self.sessions.write().await.insert(request.session_id, session);
 
         // Generate introduction
         let intro_message = self.generate_introduction(lesson).await?;
@@ -97,21 +205,24 @@ impl InteractiveTutor {
             phase: TeachingPhase::Introduction,
             code_result: None,
             show_new_challenge: false,
+            flashcards_due: Vec::new(),
+            #[cfg(feature = "voice")]
+            audio: None,
+            #[cfg(feature = "voice")]
+            speech_marks: Vec::new(),
         })
     }
 
     async fn handle_message(&self, request: TutorRequest) -> Result<TutorResponse> {
         let student_message = request.message.context("Message is required")?;
 
+        // Ensure the session is hydrated into the in-memory cache before
+        // recording the student's turn.
+        self.get_session(request.session_id).await?;
         self.add_message(request.session_id, MessageRole::Student, &student_message)
             .await;
 
-        let sessions = self.sessions.read().await;
-        let session = sessions
-            .get(&request.session_id)
-            .context("Session not found")?
-            .clone();
-        drop(sessions);
+        let session = self.get_session(request.session_id).await?;
 
         let lesson = self
             .lesson_manager
@@ -138,18 +249,18 @@ impl InteractiveTutor {
             phase: session.teaching_phase.clone(),
             code_result: None,
             show_new_challenge: false,
+            flashcards_due: Vec::new(),
+            #[cfg(feature = "voice")]
+            audio: None,
+            #[cfg(feature = "voice")]
+            speech_marks: Vec::new(),
         })
     }
 
     async fn handle_code_submission(&self, request: TutorRequest) -> Result<TutorResponse> {
         let code = request.code.context("Code is required")?;
 
-        let sessions = self.sessions.read().await;
-        let session = sessions
-            .get(&request.session_id)
-            .context("Session not found")?
-            .clone();
-        drop(sessions);
+        let session = self.get_session(request.session_id).await?;
 
         let lesson = self
             .lesson_manager
@@ -159,6 +270,9 @@ impl InteractiveTutor {
         // Execute the code
         let exec_result = self.code_executor.execute_code(&code, 10).await?;
 
+        self.record_submission(&session, request.exercise_id.as_deref(), &code, &exec_result)
+            .await?;
+
         // Evaluate the submission
         let (feedback, new_phase, show_new_challenge) = self
             .evaluate_submission(&session, lesson, &code, &exec_result)
@@ -168,6 +282,25 @@ impl InteractiveTutor {
         self.update_phase(request.session_id, new_phase.clone())
             .await;
 
+        if matches!(new_phase, TeachingPhase::Mastery) {
+            self.mark_exercise_complete(
+                request.session_id,
+                &session.current_lesson_id,
+                request.exercise_id.as_deref().unwrap_or(&session.current_lesson_id),
+            )
+            .await?;
+        } else if matches!(new_phase, TeachingPhase::Challenge) {
+            // The student either hit an execution error or wasn't judged to
+            // have demonstrated mastery yet - either way, worth a flashcard.
+            let what_went_wrong = if exec_result.success {
+                "Didn't yet demonstrate understanding of the challenge"
+            } else {
+                exec_result.error.as_deref().unwrap_or("Unknown error")
+            };
+            self.record_learning_moment(request.session_id, lesson, &session, what_went_wrong)
+                .await;
+        }
+
         self.add_message(request.session_id, MessageRole::Tutor, &feedback)
             .await;
 
@@ -176,16 +309,16 @@ impl InteractiveTutor {
             phase: new_phase,
             code_result: Some(exec_result),
             show_new_challenge,
+            flashcards_due: Vec::new(),
+            #[cfg(feature = "voice")]
+            audio: None,
+            #[cfg(feature = "voice")]
+            speech_marks: Vec::new(),
         })
     }
 
     async fn provide_hint(&self, request: TutorRequest) -> Result<TutorResponse> {
-        let sessions = self.sessions.read().await;
-        let session = sessions
-            .get(&request.session_id)
-            .context("Session not found")?
-            .clone();
-        drop(sessions);
+        let session = self.get_session(request.session_id).await?;
 
         let lesson = self
             .lesson_manager
@@ -210,16 +343,16 @@ impl InteractiveTutor {
             phase: TeachingPhase::Helping,
             code_result: None,
             show_new_challenge: false,
+            flashcards_due: Vec::new(),
+            #[cfg(feature = "voice")]
+            audio: None,
+            #[cfg(feature = "voice")]
+            speech_marks: Vec::new(),
         })
     }
 
     async fn provide_walkthrough(&self, request: TutorRequest) -> Result<TutorResponse> {
-        let sessions = self.sessions.read().await;
-        let session = sessions
-            .get(&request.session_id)
-            .context("Session not found")?
-            .clone();
-        drop(sessions);
+        let session = self.get_session(request.session_id).await?;
 
         let lesson = self
             .lesson_manager
@@ -247,114 +380,280 @@ impl InteractiveTutor {
             phase: TeachingPhase::Walkthrough,
             code_result: None,
             show_new_challenge: true, // Indicate new challenge will be needed
+            flashcards_due: Vec::new(),
+            #[cfg(feature = "voice")]
+            audio: None,
+            #[cfg(feature = "voice")]
+            speech_marks: Vec::new(),
         })
     }
 
-    async fn generate_introduction(&self, lesson: &Lesson) -> Result<String> {
-        if self.api_key.is_none() {
-            return Ok(self.simple_introduction(lesson));
-        }
+    fn build_introduction_prompt(&self, lesson: &Lesson) -> Result<String> {
+        self.prompts.render(
+            "introduction",
+            lesson.prompt_overrides.get("introduction").map(|s| s.as_str()),
+            context! { lesson },
+        )
+    }
 
-        let prompt = format!(
-            r#"You are an enthusiastic and encouraging Python programming tutor.
+    /// Streaming counterpart to `handle_request`. `StartLesson`,
+    /// `RequestHint` and `RequestWalkthrough` stream incremental text;
+    /// `SendMessage` runs the tool-calling agent loop and delivers its reply
+    /// as a single delta; other request types still run to completion and
+    /// are surfaced as a single `Done` event.
+    pub async fn handle_request_stream(
+        self: Arc<Self>,
+        request: TutorRequest,
+    ) -> Result<BoxStream<'static, Result<TutorStreamEvent>>> {
+        match request.request_type {
+            TutorRequestType::StartLesson => self.start_lesson_stream(request).await,
+            TutorRequestType::SendMessage => self.handle_message_stream(request).await,
+            TutorRequestType::RequestHint => self.provide_hint_stream(request).await,
+            TutorRequestType::RequestWalkthrough => self.provide_walkthrough_stream(request).await,
+            _ => {
+                let response = self.handle_request(request).await?;
+                Ok(futures::stream::once(async move {
+                    Ok(TutorStreamEvent::Done(Box::new(response)))
+                })
+                .boxed())
+            }
+        }
+    }
 
-Introduce this lesson to a beginner:
+    async fn provide_hint_stream(
+        self: Arc<Self>,
+        request: TutorRequest,
+    ) -> Result<BoxStream<'static, Result<TutorStreamEvent>>> {
+        let session = self.get_session(request.session_id).await?;
 
-Lesson: {}
-Description: {}
-Objectives: {}
+        let lesson = self
+            .lesson_manager
+            .get_lesson(&session.current_lesson_id)
+            .context("Lesson not found")?;
 
-Provide a warm, encouraging introduction that:
-1. Explains what they'll learn
-2. Why it's useful
-3. Gets them excited to start
-4. Is conversational and friendly
+        if let Some(challenge) = &session.current_challenge {
+            let mut updated_challenge = challenge.clone();
+            updated_challenge.hints_given += 1;
+            self.update_challenge(request.session_id, updated_challenge)
+                .await;
+        }
 
-Keep it to 2-3 paragraphs. End by asking if they're ready to learn about the first concept."#,
-            lesson.title,
-            lesson.description,
-            lesson.objectives.join(", ")
-        );
+        let deltas = self.generate_socratic_hint_stream(&session, lesson).await?;
 
-        self.call_claude(&prompt).await
+        Ok(self.stream_tutor_reply(deltas, request.session_id, TeachingPhase::Helping, false))
     }
 
-    async fn continue_teaching(
-        &self,
-        session: &SessionState,
-        lesson: &Lesson,
-        student_message: &str,
-    ) -> Result<String> {
-        if self.api_key.is_none() {
-            return Ok(self.simple_teaching_response(lesson, student_message));
+    async fn provide_walkthrough_stream(
+        self: Arc<Self>,
+        request: TutorRequest,
+    ) -> Result<BoxStream<'static, Result<TutorStreamEvent>>> {
+        let session = self.get_session(request.session_id).await?;
+
+        let lesson = self
+            .lesson_manager
+            .get_lesson(&session.current_lesson_id)
+            .context("Lesson not found")?
+            .clone();
+
+        if let Some(challenge) = &session.current_challenge {
+            let mut updated_challenge = challenge.clone();
+            updated_challenge.walkthrough_used = true;
+            self.update_challenge(request.session_id, updated_challenge)
+                .await;
         }
 
-        let conversation_context = self.build_conversation_context(session);
+        self.update_phase(request.session_id, TeachingPhase::Walkthrough)
+            .await;
 
-        let prompt = format!(
-            r#"You are a patient Python programming tutor using the Socratic method.
+        let deltas = self.generate_walkthrough_stream(&session, &lesson).await?;
 
-Lesson: {}
-Objectives: {}
-Key Concepts: {}
+        Ok(self.stream_tutor_reply(
+            deltas,
+            request.session_id,
+            TeachingPhase::Walkthrough,
+            true,
+        ))
+    }
 
-Previous conversation:
-{}
+    async fn start_lesson_stream(
+        self: Arc<Self>,
+        request: TutorRequest,
+    ) -> Result<BoxStream<'static, Result<TutorStreamEvent>>> {
+        let lesson = self
+            .lesson_manager
+            .get_lesson(&request.lesson_id)
+            .context("Lesson not found")?
+            .clone();
 
-Student's latest message: "{}"
+        let session = SessionState {
+            session_id: request.session_id,
+            current_lesson_id: request.lesson_id.clone(),
+            teaching_phase: TeachingPhase::Introduction,
+            conversation_history: Vec::new(),
+            current_challenge: None,
+            completed_exercises: Vec::new(),
+            code_history: Vec::new(),
+        };
 
-Continue teaching about these concepts. When you've covered the key concepts and the student seems ready, present them with a coding challenge to demonstrate their understanding.
+        self.store.save_session(&session).await?;
+        self.sessions.write().await.insert(request.session_id, session);
 
-IMPORTANT:
-- Teach one concept at a time
-- Use simple examples
-- Check for understanding
-- When ready, transition to presenting a challenge
-- The challenge should test if they understand the objectives
+        let deltas = self.generate_introduction_stream(&lesson).await?;
 
-Your response:"#,
-            lesson.title,
-            lesson.objectives.join(", "),
-            lesson.concepts.join(", "),
-            conversation_context,
-            student_message
-        );
+        Ok(self.stream_tutor_reply(
+            deltas,
+            request.session_id,
+            TeachingPhase::Introduction,
+            false,
+        ))
+    }
 
-        let response = self.call_claude(&prompt).await?;
+    async fn handle_message_stream(
+        self: Arc<Self>,
+        request: TutorRequest,
+    ) -> Result<BoxStream<'static, Result<TutorStreamEvent>>> {
+        let student_message = request.message.clone().context("Message is required")?;
 
-        // Check if AI is presenting a challenge
-        if response.to_lowercase().contains("challenge")
-            || response.to_lowercase().contains("try to")
-            || response.to_lowercase().contains("your task")
-        {
-            // Transition to challenge phase
-            let challenge = Challenge {
-                description: response.clone(),
-                validation_hints: Vec::new(),
-                hints_given: 0,
-                walkthrough_used: false,
-            };
+        self.get_session(request.session_id).await?;
+        self.add_message(request.session_id, MessageRole::Student, &student_message)
+            .await;
 
-            self.update_challenge(session.session_id, challenge).await;
-            self.update_phase(session.session_id, TeachingPhase::Challenge)
-                .await;
-        }
+        let session = self.get_session(request.session_id).await?;
 
-        Ok(response)
+        let lesson = self
+            .lesson_manager
+            .get_lesson(&session.current_lesson_id)
+            .context("Lesson not found")?
+            .clone();
+
+        let phase = session.teaching_phase.clone();
+        // These phases run the same tool-calling agent loop as the
+        // non-streaming path (`continue_teaching`/`provide_socratic_guidance`),
+        // so the model can call `present_challenge`/`mark_mastery` regardless
+        // of which endpoint is used. The loop itself isn't incremental, so its
+        // reply is delivered as a single delta rather than token-by-token.
+        let deltas: BoxStream<'static, Result<String>> = match session.teaching_phase {
+            TeachingPhase::Introduction | TeachingPhase::Teaching => {
+                let reply = self
+                    .continue_teaching(&session, &lesson, &student_message)
+                    .await?;
+                futures::stream::once(async move { Ok(reply) }).boxed()
+            }
+            TeachingPhase::Challenge | TeachingPhase::NewChallenge => {
+                let reply = self
+                    .provide_socratic_guidance(&session, &lesson, &student_message)
+                    .await?;
+                futures::stream::once(async move { Ok(reply) }).boxed()
+            }
+            _ => futures::stream::once(async move {
+                Ok("Please submit your code to continue.".to_string())
+            })
+            .boxed(),
+        };
+
+        Ok(self.stream_tutor_reply(deltas, request.session_id, phase, false))
     }
 
-    async fn provide_socratic_guidance(
+    /// Wires a raw text-delta stream into a `TutorStreamEvent` stream: every
+    /// delta is forwarded as-is, then once the underlying stream ends the
+    /// accumulated text is persisted to conversation history, and a final
+    /// `Done` event carries the assembled `TutorResponse`.
+    fn stream_tutor_reply(
+        self: Arc<Self>,
+        deltas: BoxStream<'static, Result<String>>,
+        session_id: Uuid,
+        phase: TeachingPhase,
+        show_new_challenge: bool,
+    ) -> BoxStream<'static, Result<TutorStreamEvent>> {
+        let tutor = self;
+        let state = (deltas, String::new(), phase, false);
+
+        futures::stream::unfold(state, move |(mut deltas, mut acc, phase, done)| {
+            let tutor = tutor.clone();
+            async move {
+                if done {
+                    return None;
+                }
+
+                match deltas.next().await {
+                    Some(Ok(chunk)) => {
+                        acc.push_str(&chunk);
+                        Some((
+                            Ok(TutorStreamEvent::Delta(chunk)),
+                            (deltas, acc, phase, false),
+                        ))
+                    }
+                    Some(Err(e)) => Some((Err(e), (deltas, acc, phase, true))),
+                    None => {
+                        tutor
+                            .add_message(session_id, MessageRole::Tutor, &acc)
+                            .await;
+                        let response = TutorResponse {
+                            message: acc.clone(),
+                            phase: phase.clone(),
+                            code_result: None,
+                            show_new_challenge,
+                            flashcards_due: Vec::new(),
+                            #[cfg(feature = "voice")]
+                            audio: None,
+                            #[cfg(feature = "voice")]
+                            speech_marks: Vec::new(),
+                        };
+                        Some((
+                            Ok(TutorStreamEvent::Done(Box::new(response))),
+                            (deltas, acc, phase, true),
+                        ))
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+    async fn generate_introduction(&self, lesson: &Lesson) -> Result<String> {
+        let prompt = self.build_introduction_prompt(lesson)?;
+        self.provider.complete(&prompt).await
+    }
+
+    async fn generate_introduction_stream(
+        &self,
+        lesson: &Lesson,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let prompt = self.build_introduction_prompt(lesson)?;
+        self.provider.complete_stream(&prompt).await
+    }
+
+    fn build_teaching_prompt(
         &self,
         session: &SessionState,
         lesson: &Lesson,
         student_message: &str,
     ) -> Result<String> {
-        if self.api_key.is_none() {
-            return Ok(
-                "Think about the problem step by step. What do you need to do first?".to_string(),
-            );
-        }
+        let conversation_context = self.build_conversation_context(session);
 
+        self.prompts.render(
+            "teaching",
+            lesson.prompt_overrides.get("teaching").map(|s| s.as_str()),
+            context! { lesson, conversation_context, student_message },
+        )
+    }
+
+    async fn continue_teaching(
+        &self,
+        session: &SessionState,
+        lesson: &Lesson,
+        student_message: &str,
+    ) -> Result<String> {
+        let prompt = self.build_teaching_prompt(session, lesson, student_message)?;
+        self.run_agent_turn(session, prompt).await
+    }
+
+    fn build_socratic_prompt(
+        &self,
+        session: &SessionState,
+        lesson: &Lesson,
+        student_message: &str,
+    ) -> Result<String> {
         let challenge_desc = session
             .current_challenge
             .as_ref()
@@ -363,41 +662,158 @@ Your response:"#,
 
         let conversation_context = self.build_conversation_context(session);
 
-        let prompt = format!(
-            r#"You are a Python tutor helping a student solve a coding challenge using the Socratic method.
-
-Lesson Objectives: {}
-Challenge: {}
-
-Conversation history:
-{}
-
-Student says: "{}"
+        self.prompts.render(
+            "socratic_guidance",
+            lesson.prompt_overrides.get("socratic_guidance").map(|s| s.as_str()),
+            context! { lesson, challenge_desc, conversation_context, student_message },
+        )
+    }
 
-Provide guidance using the Socratic method:
-- Ask guiding questions rather than giving answers
-- Help them think through the problem
-- Suggest approaches without solving it for them
-- Encourage them to try things
-- NEVER give them the direct answer or complete solution
+    async fn provide_socratic_guidance(
+        &self,
+        session: &SessionState,
+        lesson: &Lesson,
+        student_message: &str,
+    ) -> Result<String> {
+        let prompt = self.build_socratic_prompt(session, lesson, student_message)?;
+        self.run_agent_turn(session, prompt).await
+    }
 
-If they seem very stuck, remind them they can request a hint or walkthrough.
+    /// Tools available to the model while it's driving a `handle_message`
+    /// turn, in place of the old `response.contains("challenge")`-style
+    /// keyword detection: `execute_python` lets it run illustrative snippets
+    /// mid-explanation, and `present_challenge`/`mark_mastery` let it decide
+    /// phase transitions structurally.
+    fn agent_tools() -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition {
+                name: "execute_python",
+                description: "Runs a short Python snippet and returns its output, for demonstrating a concept mid-explanation.",
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "description": "The Python code to run."
+                        }
+                    },
+                    "required": ["code"]
+                }),
+            },
+            ToolDefinition {
+                name: "present_challenge",
+                description: "Presents the student with a coding challenge, moving the session into the Challenge phase.",
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "description": {
+                            "type": "string",
+                            "description": "The challenge prompt to show the student."
+                        }
+                    },
+                    "required": ["description"]
+                }),
+            },
+            ToolDefinition {
+                name: "mark_mastery",
+                description: "Marks the student as having demonstrated mastery of the current lesson's objectives.",
+                input_schema: json!({ "type": "object", "properties": {} }),
+            },
+        ]
+    }
 
-Your response:"#,
-            lesson.objectives.join(", "),
-            challenge_desc,
-            conversation_context,
-            student_message
-        );
+    /// Runs a tool the model asked for during an agent step and returns the
+    /// text to feed back as its `tool_result`.
+    async fn run_agent_tool(&self, session: &SessionState, name: &str, input: serde_json::Value) -> String {
+        match name {
+            "execute_python" => {
+                let code = input.get("code").and_then(|c| c.as_str()).unwrap_or_default();
+                match self.code_executor.execute_code(code, 10).await {
+                    Ok(result) if result.success => format!("Output:\n{}", result.output),
+                    Ok(result) => format!(
+                        "Error:\n{}",
+                        result.error.unwrap_or_else(|| "Unknown error".to_string())
+                    ),
+                    Err(e) => format!("Execution failed: {}", e),
+                }
+            }
+            "present_challenge" => {
+                let description = input
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or_default();
+
+                let challenge = Challenge {
+                    description: description.to_string(),
+                    validation_hints: Vec::new(),
+                    hints_given: 0,
+                    walkthrough_used: false,
+                };
+                self.update_challenge(session.session_id, challenge).await;
+                self.update_phase(session.session_id, TeachingPhase::Challenge)
+                    .await;
 
-        self.call_claude(&prompt).await
+                "Challenge presented to the student.".to_string()
+            }
+            "mark_mastery" => {
+                self.update_phase(session.session_id, TeachingPhase::Mastery)
+                    .await;
+                if let Err(e) = self
+                    .mark_exercise_complete(
+                        session.session_id,
+                        &session.current_lesson_id,
+                        &session.current_lesson_id,
+                    )
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to mark exercise complete for session {}: {}",
+                        session.session_id,
+                        e
+                    );
+                }
+
+                "Mastery recorded.".to_string()
+            }
+            other => format!("Unknown tool: {}", other),
+        }
     }
 
-    async fn generate_socratic_hint(&self, session: &SessionState, lesson: &Lesson) -> Result<String> {
-        if self.api_key.is_none() {
-            return Ok("Try breaking down the problem into smaller steps. What's the first thing you need to do?".to_string());
+    /// Drives a tool-calling conversation to completion: sends `prompt` with
+    /// `agent_tools()` available and, while the model keeps asking for tool
+    /// calls, runs them and feeds the results back until it returns plain
+    /// text (or the loop's step budget runs out, as a guard against a model
+    /// that never stops calling tools).
+    async fn run_agent_turn(&self, session: &SessionState, prompt: String) -> Result<String> {
+        let tools = Self::agent_tools();
+        let mut history = vec![AgentTurn::user_text(prompt)];
+
+        for _ in 0..5 {
+            match self.provider.complete_agent_step(&history, &tools).await? {
+                AgentStep::Text(text) => return Ok(text),
+                AgentStep::ToolUse(calls) => {
+                    history.push(AgentTurn::assistant_tool_use(calls.clone()));
+
+                    let mut results = Vec::with_capacity(calls.len());
+                    for call in calls {
+                        let AgentBlock::ToolUse { id, name, input } = call else {
+                            continue;
+                        };
+                        let content = self.run_agent_tool(session, &name, input).await;
+                        results.push(AgentBlock::ToolResult {
+                            tool_use_id: id,
+                            content,
+                        });
+                    }
+                    history.push(AgentTurn::tool_results(results));
+                }
+            }
         }
 
+        anyhow::bail!("Agent loop did not produce a final reply in time")
+    }
+
+    fn build_hint_prompt(&self, session: &SessionState, lesson: &Lesson) -> Result<String> {
         let challenge_desc = session
             .current_challenge
             .as_ref()
@@ -410,53 +826,53 @@ Your response:"#,
             .map(|c| c.hints_given)
             .unwrap_or(0);
 
-        let prompt = format!(
-            r#"You are providing a hint to a student stuck on a Python coding challenge.
-
-Challenge: {}
-Hints already given: {}
-
-Provide a helpful hint that:
-- Points them in the right direction
-- Doesn't give away the answer
-- Focuses on ONE specific aspect they should consider
-- Gets progressively more specific if multiple hints have been given
-
-Your hint:"#,
-            challenge_desc, hints_given
-        );
+        self.prompts.render(
+            "hint",
+            lesson.prompt_overrides.get("hint").map(|s| s.as_str()),
+            context! { lesson, challenge_desc, hints_given },
+        )
+    }
 
-        self.call_claude(&prompt).await
+    async fn generate_socratic_hint(&self, session: &SessionState, lesson: &Lesson) -> Result<String> {
+        let prompt = self.build_hint_prompt(session, lesson)?;
+        self.provider.complete(&prompt).await
     }
 
-    async fn generate_walkthrough(&self, session: &SessionState, lesson: &Lesson) -> Result<String> {
-        if self.api_key.is_none() {
-            return Ok("Let me walk you through this step by step. Since I've helped you through this, I'll give you a new challenge to demonstrate your understanding.".to_string());
-        }
+    async fn generate_socratic_hint_stream(
+        &self,
+        session: &SessionState,
+        lesson: &Lesson,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let prompt = self.build_hint_prompt(session, lesson)?;
+        self.provider.complete_stream(&prompt).await
+    }
 
+    fn build_walkthrough_prompt(&self, session: &SessionState, lesson: &Lesson) -> Result<String> {
         let challenge_desc = session
             .current_challenge
             .as_ref()
             .map(|c| c.description.clone())
             .unwrap_or_default();
 
-        let prompt = format!(
-            r#"You are walking a student through solving a Python coding challenge step-by-step.
-
-Lesson: {}
-Challenge: {}
-
-Provide a step-by-step walkthrough that:
-1. Breaks down the problem
-2. Explains each step of the solution
-3. Shows the code with detailed explanations
-4. Ends by explaining that since you walked them through this, you'll now give them a NEW, DIFFERENT challenge to prove they understood
+        self.prompts.render(
+            "walkthrough",
+            lesson.prompt_overrides.get("walkthrough").map(|s| s.as_str()),
+            context! { lesson, challenge_desc },
+        )
+    }
 
-Your walkthrough:"#,
-            lesson.title, challenge_desc
-        );
+    async fn generate_walkthrough(&self, session: &SessionState, lesson: &Lesson) -> Result<String> {
+        let prompt = self.build_walkthrough_prompt(session, lesson)?;
+        self.provider.complete(&prompt).await
+    }
 
-        self.call_claude(&prompt).await
+    async fn generate_walkthrough_stream(
+        &self,
+        session: &SessionState,
+        lesson: &Lesson,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let prompt = self.build_walkthrough_prompt(session, lesson)?;
+        self.provider.complete_stream(&prompt).await
     }
 
     async fn evaluate_submission(
@@ -519,53 +935,20 @@ Your walkthrough:"#,
         code: &str,
         exec_result: &ExecutionResult,
     ) -> Result<(bool, String)> {
-        if self.api_key.is_none() {
-            return Ok((
-                true,
-                format!(
-                    "Great job! Your code works! Output:\n{}\n\nYou've demonstrated understanding of this lesson!",
-                    exec_result.output
-                ),
-            ));
-        }
-
         let challenge_desc = session
             .current_challenge
             .as_ref()
             .map(|c| c.description.clone())
             .unwrap_or_default();
 
-        let prompt = format!(
-            r#"You are evaluating a student's Python code for a learning challenge.
-
-Lesson Objectives: {}
-Challenge: {}
-
-Student's Code:
-```python
-{}
-```
-
-Output: {}
-
-Evaluate if the student has demonstrated understanding of the lesson objectives:
-1. Does the code solve the challenge correctly?
-2. Does it show understanding of the key concepts?
-3. Is the approach reasonable for a beginner?
+        let output = &exec_result.output;
+        let prompt = self.prompts.render(
+            "mastery_evaluation",
+            lesson.prompt_overrides.get("mastery_evaluation").map(|s| s.as_str()),
+            context! { lesson, challenge_desc, code, output },
+        )?;
 
-Respond in this format:
-MASTERY: [YES or NO]
-FEEDBACK: [Your encouraging feedback]
-
-If YES: Congratulate them and explain what they did well
-If NO: Provide constructive feedback on what to improve, without giving the answer"#,
-            lesson.objectives.join(", "),
-            challenge_desc,
-            code,
-            exec_result.output
-        );
-
-        let response = self.call_claude(&prompt).await?;
+        let response = self.provider.complete(&prompt).await?;
 
         let mastered = response.to_uppercase().contains("MASTERY: YES");
         let feedback = response
@@ -586,37 +969,19 @@ If NO: Provide constructive feedback on what to improve, without giving the answ
         lesson: &Lesson,
         previous_code: &str,
     ) -> Result<String> {
-        if self.api_key.is_none() {
-            return Ok(format!(
-                "Great! Now try creating a similar solution but for a different scenario. You've got this!"
-            ));
-        }
-
         let old_challenge = session
             .current_challenge
             .as_ref()
             .map(|c| c.description.clone())
             .unwrap_or_default();
 
-        let prompt = format!(
-            r#"You are a Python tutor. The student needed a walkthrough for this challenge:
-
-Previous Challenge: {}
-
-Now generate a NEW, DIFFERENT challenge that:
-1. Tests the SAME concepts and objectives: {}
-2. Is similar in difficulty
-3. Uses a different scenario or example
-4. Is NOT the same as the previous challenge
+        let prompt = self.prompts.render(
+            "new_challenge",
+            lesson.prompt_overrides.get("new_challenge").map(|s| s.as_str()),
+            context! { lesson, old_challenge },
+        )?;
 
-Explain that since they needed help with the first challenge, this new one will let them demonstrate they truly understand the concept.
-
-Your new challenge:"#,
-            old_challenge,
-            lesson.objectives.join(", ")
-        );
-
-        let new_challenge_text = self.call_claude(&prompt).await?;
+        let new_challenge_text = self.provider.complete(&prompt).await?;
 
         // Create new challenge
         let challenge = Challenge {
@@ -631,70 +996,6 @@ Your new challenge:"#,
         Ok(new_challenge_text)
     }
 
-    fn simple_introduction(&self, lesson: &Lesson) -> String {
-        format!(
-            "Welcome to {}!\n\n{}\n\nIn this lesson, you'll learn:\n{}\n\nAre you ready to get started?",
-            lesson.title,
-            lesson.description,
-            lesson.objectives
-                .iter()
-                .map(|obj| format!("â€¢ {}", obj))
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
-    }
-
-    fn simple_teaching_response(&self, lesson: &Lesson, _message: &str) -> String {
-        format!(
-            "Let's learn about {}. Try writing some code to practice the concepts.",
-            lesson.title
-        )
-    }
-
-    async fn call_claude(&self, prompt: &str) -> Result<String> {
-        let api_key = self
-            .api_key
-            .as_ref()
-            .context("API key not available")?;
-
-        let request = ClaudeRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 2048,
-            messages: vec![ClaudeMessage {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-        };
-
-        let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send request to Claude API")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Claude API error ({}): {}", status, error_text);
-        }
-
-        let claude_response: ClaudeResponse = response
-            .json()
-            .await
-            .context("Failed to parse Claude API response")?;
-
-        Ok(claude_response
-            .content
-            .first()
-            .map(|c| c.text.clone())
-            .unwrap_or_else(|| "I'm having trouble responding right now.".to_string()))
-    }
-
     fn build_conversation_context(&self, session: &SessionState) -> String {
         session
             .conversation_history
@@ -713,30 +1014,202 @@ Your new challenge:"#,
     }
 
     async fn add_message(&self, session_id: Uuid, role: MessageRole, content: &str) {
-        let mut sessions = self.sessions.write().await;
-        if let Some(session) = sessions.get_mut(&session_id) {
-            session.conversation_history.push(Message {
-                role,
-                content: content.to_string(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-            });
+        let message = Message {
+            role,
+            content: content.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+
+        {
+            let mut sessions = self.sessions.write().await;
+            if let Some(session) = sessions.get_mut(&session_id) {
+                session.conversation_history.push(message.clone());
+            }
+        }
+
+        if let Err(e) = self.store.append_message(session_id, &message).await {
+            tracing::warn!("Failed to persist message for session {}: {}", session_id, e);
         }
     }
 
     async fn update_phase(&self, session_id: Uuid, phase: TeachingPhase) {
-        let mut sessions = self.sessions.write().await;
-        if let Some(session) = sessions.get_mut(&session_id) {
-            session.teaching_phase = phase;
+        let updated = {
+            let mut sessions = self.sessions.write().await;
+            sessions.get_mut(&session_id).map(|session| {
+                session.teaching_phase = phase;
+                session.clone()
+            })
+        };
+
+        if let Some(session) = updated {
+            if let Err(e) = self.store.save_session(&session).await {
+                tracing::warn!("Failed to persist phase for session {}: {}", session_id, e);
+            }
         }
     }
 
     async fn update_challenge(&self, session_id: Uuid, challenge: Challenge) {
-        let mut sessions = self.sessions.write().await;
-        if let Some(session) = sessions.get_mut(&session_id) {
-            session.current_challenge = Some(challenge);
+        let updated = {
+            let mut sessions = self.sessions.write().await;
+            sessions.get_mut(&session_id).map(|session| {
+                session.current_challenge = Some(challenge);
+                session.clone()
+            })
+        };
+
+        if let Some(session) = updated {
+            if let Err(e) = self.store.save_session(&session).await {
+                tracing::warn!(
+                    "Failed to persist challenge for session {}: {}",
+                    session_id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Persists a code submission and appends it to the session's code
+    /// history, mirroring a `Run`/`Submit` history the way leetcode-style
+    /// trackers keep every attempt.
+    async fn record_submission(
+        &self,
+        session: &SessionState,
+        exercise_id: Option<&str>,
+        code: &str,
+        exec_result: &ExecutionResult,
+    ) -> Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.store
+            .record_submission(
+                session.session_id,
+                &session.current_lesson_id,
+                exercise_id,
+                code,
+                exec_result.success,
+                timestamp,
+            )
+            .await?;
+
+        if let Some(session) = self.sessions.write().await.get_mut(&session.session_id) {
+            session.code_history.push(code.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Marks an exercise mastered, the analog of leetcode-cli's post-accept
+    /// update: it's recorded in `progress` and reflected in the session's
+    /// `completed_exercises` so a resumed session remembers it.
+    async fn mark_exercise_complete(
+        &self,
+        session_id: Uuid,
+        lesson_id: &str,
+        exercise_id: &str,
+    ) -> Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.store
+            .mark_exercise_complete(session_id, lesson_id, exercise_id, timestamp)
+            .await?;
+
+        if let Some(session) = self.sessions.write().await.get_mut(&session_id) {
+            if !session
+                .completed_exercises
+                .iter()
+                .any(|id| id == exercise_id)
+            {
+                session.completed_exercises.push(exercise_id.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Distills a mistake (a failed execution or an unmastered challenge)
+    /// into a flashcard and persists it for later spaced-repetition review.
+    /// Built heuristically from data already on hand rather than another LLM
+    /// call, the same way `parse_ai_feedback` falls back to keyword matching
+    /// elsewhere in this codebase.
+    async fn record_learning_moment(
+        &self,
+        session_id: Uuid,
+        lesson: &Lesson,
+        session: &SessionState,
+        what_went_wrong: &str,
+    ) {
+        let concept = lesson
+            .concepts
+            .iter()
+            .find(|concept| what_went_wrong.to_lowercase().contains(&concept.to_lowercase()))
+            .cloned()
+            .unwrap_or_else(|| lesson.title.clone());
+
+        let front = session
+            .current_challenge
+            .as_ref()
+            .map(|c| c.description.clone())
+            .unwrap_or_else(|| lesson.description.clone());
+
+        let back = format!(
+            "What went wrong: {}\n\nKey concept: {}\nObjectives: {}",
+            what_went_wrong,
+            concept,
+            lesson.objectives.join(", ")
+        );
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let card = flashcards::new_flashcard(concept, front, back, now);
+
+        if let Err(e) = self.store.save_flashcard(session_id, &card).await {
+            tracing::warn!("Failed to save flashcard for session {}: {}", session_id, e);
         }
     }
+
+    /// Grades the reviewed card (if any) and returns the cards now due for
+    /// review, oldest first.
+    async fn review_flashcards(&self, request: TutorRequest) -> Result<TutorResponse> {
+        let session = self.get_session(request.session_id).await?;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        if let Some(review) = request.flashcard_review {
+            let mut due = self.store.due_flashcards(request.session_id, now).await?;
+            if let Some(pos) = due.iter().position(|c| c.id == review.flashcard_id) {
+                let mut card = due.remove(pos);
+                flashcards::schedule_review(&mut card, review.grade, now);
+                self.store.save_flashcard(request.session_id, &card).await?;
+            }
+        }
+
+        let flashcards_due = self.store.due_flashcards(request.session_id, now).await?;
+
+        Ok(TutorResponse {
+            message: format!("{} flashcard(s) due for review.", flashcards_due.len()),
+            phase: session.teaching_phase,
+            code_result: None,
+            show_new_challenge: false,
+            flashcards_due,
+            #[cfg(feature = "voice")]
+            audio: None,
+            #[cfg(feature = "voice")]
+            speech_marks: Vec::new(),
+        })
+    }
 }
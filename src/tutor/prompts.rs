@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use minijinja::Environment;
+use std::path::Path;
+
+const INTRODUCTION: &str = include_str!("prompts/introduction.jinja");
+const TEACHING: &str = include_str!("prompts/teaching.jinja");
+const SOCRATIC_GUIDANCE: &str = include_str!("prompts/socratic_guidance.jinja");
+const HINT: &str = include_str!("prompts/hint.jinja");
+const WALKTHROUGH: &str = include_str!("prompts/walkthrough.jinja");
+const MASTERY_EVALUATION: &str = include_str!("prompts/mastery_evaluation.jinja");
+const NEW_CHALLENGE: &str = include_str!("prompts/new_challenge.jinja");
+
+/// Renders the tutor's teaching/hinting/evaluation prompts from named
+/// minijinja templates instead of hardcoded `format!` strings, so tuning the
+/// tutor's persona, porting it to another subject/language, or localizing it
+/// doesn't require recompiling. Ships with an embedded default template for
+/// each named prompt below, overridable by dropping a same-named `.jinja`
+/// file into a configured directory (see `load_overrides`), or per-lesson
+/// via `Lesson::prompt_overrides`.
+///
+/// Every template is rendered with a stable variable contract: `lesson`
+/// (the full `Lesson`), plus whatever else that prompt needs -
+/// `conversation_context`, `student_message`, `challenge_desc`,
+/// `hints_given`, `code`, `output`, `old_challenge`.
+pub struct PromptEngine {
+    env: Environment<'static>,
+}
+
+impl PromptEngine {
+    /// Builds the engine with the embedded default template set.
+    pub fn new() -> Self {
+        let mut env = Environment::new();
+        env.add_template("introduction", INTRODUCTION)
+            .expect("embedded introduction template is valid");
+        env.add_template("teaching", TEACHING)
+            .expect("embedded teaching template is valid");
+        env.add_template("socratic_guidance", SOCRATIC_GUIDANCE)
+            .expect("embedded socratic_guidance template is valid");
+        env.add_template("hint", HINT)
+            .expect("embedded hint template is valid");
+        env.add_template("walkthrough", WALKTHROUGH)
+            .expect("embedded walkthrough template is valid");
+        env.add_template("mastery_evaluation", MASTERY_EVALUATION)
+            .expect("embedded mastery_evaluation template is valid");
+        env.add_template("new_challenge", NEW_CHALLENGE)
+            .expect("embedded new_challenge template is valid");
+
+        Self { env }
+    }
+
+    /// Overrides any embedded templates with same-named `.jinja` files found
+    /// in `dir`. A missing directory isn't an error - it just means no
+    /// overrides are configured, the same as `LessonManager::load_lessons`.
+    pub fn load_overrides<P: AsRef<Path>>(&mut self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(dir).context("Failed to read prompts directory")? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if path.extension().and_then(|s| s.to_str()) != Some("jinja") {
+                continue;
+            }
+
+            let name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .with_context(|| format!("Prompt template file has no name: {:?}", path))?
+                .to_string();
+
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read prompt template: {:?}", path))?;
+
+            tracing::info!("Overriding prompt template '{}' from {:?}", name, path);
+            self.env
+                .add_template_owned(name.clone(), source)
+                .with_context(|| format!("Failed to parse prompt template '{}'", name))?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders a named template, preferring a per-lesson override (raw
+    /// Jinja source) over the configured/default one.
+    pub fn render(
+        &self,
+        name: &str,
+        lesson_override: Option<&str>,
+        ctx: impl serde::Serialize,
+    ) -> Result<String> {
+        if let Some(source) = lesson_override {
+            return self
+                .env
+                .render_str(source, ctx)
+                .with_context(|| format!("Failed to render lesson override for '{}'", name));
+        }
+
+        self.env
+            .get_template(name)
+            .with_context(|| format!("Unknown prompt template '{}'", name))?
+            .render(ctx)
+            .with_context(|| format!("Failed to render prompt template '{}'", name))
+    }
+}
+
+impl Default for PromptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
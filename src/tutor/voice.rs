@@ -0,0 +1,206 @@
+//! Optional voice subsystem: AWS Polly narrates tutor replies, AWS Transcribe
+//! turns a spoken student turn into text for the existing `handle_message`
+//! path. Entirely inert unless the `voice` feature is compiled in and
+//! `VoiceConfig::from_env` finds it configured - the same "opt-in via env,
+//! no-op otherwise" shape as `crate::providers::ProviderConfig`.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_sdk_polly::types::{Engine, OutputFormat, SpeechMarkType, TextType, VoiceId};
+use aws_sdk_transcribestreaming::types::{AudioEvent, AudioStream, LanguageCode, MediaEncoding};
+use bytes::Bytes;
+use futures::stream::{BoxStream, StreamExt};
+use std::sync::Arc;
+
+use crate::models::SpeechMark;
+
+/// Turns a tutor reply into spoken audio. Implemented for AWS Polly; kept as
+/// a trait (mirroring `CompletionProvider`) so another TTS backend could be
+/// swapped in without touching the tutor.
+#[async_trait]
+pub trait VoiceSynthesizer: Send + Sync {
+    /// Returns MP3 audio for `text` plus word-level speech marks so a UI can
+    /// highlight along as it plays.
+    async fn synthesize(&self, text: &str) -> Result<(Vec<u8>, Vec<SpeechMark>)>;
+}
+
+/// Turns a stream of microphone audio into text. Implemented for AWS
+/// Transcribe's streaming API.
+#[async_trait]
+pub trait VoiceTranscriber: Send + Sync {
+    async fn transcribe(&self, audio: BoxStream<'static, Result<Bytes>>) -> Result<String>;
+}
+
+/// The configured voice subsystem, built once at startup from the standard
+/// AWS credential chain.
+#[derive(Clone)]
+pub struct VoiceConfig {
+    pub narrator: Arc<dyn VoiceSynthesizer>,
+    pub transcriber: Arc<dyn VoiceTranscriber>,
+}
+
+impl VoiceConfig {
+    /// Builds the voice subsystem if `VOICE_ENABLED=1` is set, loading AWS
+    /// credentials/region the usual way (env vars, profile, IMDS). Returns
+    /// `None` if voice narration/transcription isn't wanted, so the tutor
+    /// falls back to text-only behavior.
+    pub async fn from_env() -> Option<Self> {
+        if std::env::var("VOICE_ENABLED").as_deref() != Ok("1") {
+            return None;
+        }
+
+        let aws_config = aws_config::load_from_env().await;
+        let voice_id = std::env::var("POLLY_VOICE_ID")
+            .ok()
+            .and_then(|id| VoiceId::try_parse(&id).ok())
+            .unwrap_or(VoiceId::Joanna);
+
+        Some(Self {
+            narrator: Arc::new(PollyNarrator::new(&aws_config, voice_id)),
+            transcriber: Arc::new(TranscribeStreamer::new(&aws_config)),
+        })
+    }
+}
+
+/// AWS Polly-backed `VoiceSynthesizer` using a neural voice.
+struct PollyNarrator {
+    client: aws_sdk_polly::Client,
+    voice_id: VoiceId,
+}
+
+impl PollyNarrator {
+    fn new(aws_config: &aws_config::SdkConfig, voice_id: VoiceId) -> Self {
+        Self {
+            client: aws_sdk_polly::Client::new(aws_config),
+            voice_id,
+        }
+    }
+}
+
+#[async_trait]
+impl VoiceSynthesizer for PollyNarrator {
+    async fn synthesize(&self, text: &str) -> Result<(Vec<u8>, Vec<SpeechMark>)> {
+        // Polly has no single call that returns both audio and speech marks,
+        // so these are two independent requests - run them concurrently
+        // rather than paying both round-trips back to back.
+        let audio_request = self
+            .client
+            .synthesize_speech()
+            .text(text)
+            .text_type(TextType::Text)
+            .voice_id(self.voice_id.clone())
+            .engine(Engine::Neural)
+            .output_format(OutputFormat::Mp3)
+            .send();
+
+        let marks_request = self
+            .client
+            .synthesize_speech()
+            .text(text)
+            .text_type(TextType::Text)
+            .voice_id(self.voice_id.clone())
+            .engine(Engine::Neural)
+            .output_format(OutputFormat::Json)
+            .speech_mark_types(SpeechMarkType::Word)
+            .send();
+
+        let (audio_response, marks_response) = tokio::try_join!(audio_request, marks_request)
+            .context("Polly synthesize_speech failed")?;
+
+        let audio = audio_response
+            .audio_stream
+            .collect()
+            .await
+            .context("Failed to read Polly audio stream")?
+            .into_bytes()
+            .to_vec();
+
+        let marks = marks_response
+            .audio_stream
+            .collect()
+            .await
+            .context("Failed to read Polly speech marks stream")?
+            .into_bytes();
+
+        // Speech marks come back as newline-delimited JSON, one object per marked word.
+        let speech_marks = String::from_utf8_lossy(&marks)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<SpeechMark>(line).ok())
+            .collect();
+
+        Ok((audio, speech_marks))
+    }
+}
+
+/// AWS Transcribe streaming-backed `VoiceTranscriber`.
+struct TranscribeStreamer {
+    client: aws_sdk_transcribestreaming::Client,
+}
+
+impl TranscribeStreamer {
+    fn new(aws_config: &aws_config::SdkConfig) -> Self {
+        Self {
+            client: aws_sdk_transcribestreaming::Client::new(aws_config),
+        }
+    }
+}
+
+#[async_trait]
+impl VoiceTranscriber for TranscribeStreamer {
+    async fn transcribe(&self, mut audio: BoxStream<'static, Result<Bytes>>) -> Result<String> {
+        let input_stream = async_stream::stream! {
+            while let Some(chunk) = audio.next().await {
+                match chunk {
+                    Ok(bytes) => yield Ok(AudioStream::AudioEvent(
+                        AudioEvent::builder()
+                            .audio_chunk(aws_smithy_types::Blob::new(bytes.to_vec()))
+                            .build(),
+                    )),
+                    Err(e) => yield Err(aws_sdk_transcribestreaming::error::BoxError::from(e.to_string())),
+                }
+            }
+        };
+
+        let mut output = self
+            .client
+            .start_stream_transcription()
+            .language_code(LanguageCode::EnUs)
+            .media_sample_rate_hertz(16_000)
+            .media_encoding(MediaEncoding::Pcm)
+            .audio_stream(input_stream.into())
+            .send()
+            .await
+            .context("Failed to start Transcribe streaming session")?;
+
+        let mut transcript = String::new();
+        while let Some(event) = output
+            .transcript_result_stream
+            .recv()
+            .await
+            .context("Transcribe streaming session failed")?
+        {
+            let aws_sdk_transcribestreaming::types::TranscriptResultStream::TranscriptEvent(event) = event
+            else {
+                continue;
+            };
+            let Some(results) = event.transcript.and_then(|t| t.results) else {
+                continue;
+            };
+            for result in results {
+                if result.is_partial {
+                    continue;
+                }
+                if let Some(alternative) = result.alternatives.and_then(|a| a.into_iter().next()) {
+                    if let Some(text) = alternative.transcript {
+                        if !transcript.is_empty() {
+                            transcript.push(' ');
+                        }
+                        transcript.push_str(&text);
+                    }
+                }
+            }
+        }
+
+        Ok(transcript)
+    }
+}
@@ -0,0 +1,146 @@
+use std::net::SocketAddr;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::extract::ConnectInfo;
+use axum::http::{HeaderValue, Method, Request, Response};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tower::{Layer, Service};
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Tags every request with a `Uuid`, opens a tracing span for it, and logs
+/// status + latency on completion so a slow or failing call can be traced
+/// back to its logs from the `x-request-id` header a client quotes in a bug
+/// report.
+#[derive(Clone, Copy, Default)]
+pub struct AccessLogLayer;
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLog<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessLog { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct AccessLog<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, RespBody> Service<Request<ReqBody>> for AccessLog<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<RespBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    RespBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // Swap in a clone so the service we actually call is the one that
+        // was just readied by `poll_ready`, per tower's standard middleware pattern.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let request_id = Uuid::new_v4();
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        let remote_addr = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+
+        let span = tracing::info_span!(
+            "request",
+            %request_id,
+            %method,
+            %path,
+            remote_addr = tracing::field::debug(remote_addr),
+        );
+
+        let mut guard = LatencyGuard {
+            start: Instant::now(),
+            request_id,
+            method,
+            path,
+            status: None,
+        };
+
+        async move {
+            let result = inner.call(req).await;
+
+            if let Ok(response) = &result {
+                guard.status = Some(response.status().as_u16());
+            }
+            drop(guard);
+
+            result.map(|mut response| {
+                if let Ok(value) = HeaderValue::from_str(&request_id.to_string()) {
+                    response.headers_mut().insert("x-request-id", value);
+                }
+                response
+            })
+        }
+        .instrument(span)
+        .boxed()
+    }
+}
+
+/// Logs the request's status and elapsed latency when dropped, which fires
+/// whether the request finished normally, the inner service returned an
+/// error, or the future was dropped outright (panic or cancellation) —
+/// unlike a log line at the end of the handler, this can't be skipped.
+struct LatencyGuard {
+    start: Instant,
+    request_id: Uuid,
+    method: Method,
+    path: String,
+    status: Option<u16>,
+}
+
+impl Drop for LatencyGuard {
+    fn drop(&mut self) {
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+
+        match self.status {
+            Some(status) if status >= 500 => {
+                tracing::warn!(
+                    request_id = %self.request_id,
+                    method = %self.method,
+                    path = %self.path,
+                    status,
+                    elapsed_ms,
+                    "request completed"
+                );
+            }
+            Some(status) => {
+                tracing::info!(
+                    request_id = %self.request_id,
+                    method = %self.method,
+                    path = %self.path,
+                    status,
+                    elapsed_ms,
+                    "request completed"
+                );
+            }
+            None => {
+                tracing::warn!(
+                    request_id = %self.request_id,
+                    method = %self.method,
+                    path = %self.path,
+                    elapsed_ms,
+                    "request ended without a response (error or cancellation)"
+                );
+            }
+        }
+    }
+}
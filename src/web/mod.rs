@@ -1,19 +1,27 @@
+mod access_log;
+
 use axum::{
     extract::{Path, State},
     http::StatusCode,
+    response::sse::{Event, Sse},
     response::Json,
     routing::{get, post},
     Router,
 };
+use futures::stream::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
 use tower_http::services::ServeDir;
 
-use crate::ai_assistant::AIAssistant;
+use access_log::AccessLogLayer;
+use crate::ai_assistant::{AIAssistant, ReviewStreamEvent};
 use crate::executor::CodeExecutor;
 use crate::lessons::LessonManager;
-use crate::tutor::InteractiveTutor;
-use crate::models::{AIReview, ExecutionResult, Lesson, TutorRequest, TutorResponse};
+use crate::tutor::{InteractiveTutor, TutorStreamEvent};
+use crate::models::{AIReview, ExecutionResult, Lesson, RunMode, TutorRequest, TutorResponse};
+#[cfg(feature = "voice")]
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -30,9 +38,26 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/lessons/:id", get(get_lesson))
         .route("/api/execute", post(execute_code))
         .route("/api/review", post(review_code))
+        .route("/api/review/stream", post(review_code_stream))
         .route("/api/tutor/interact", post(tutor_interact))
+        .route("/api/tutor/stream", post(tutor_interact_stream))
+        .route("/api/tutor/voice/:session_id", voice_route())
         .nest_service("/static", ServeDir::new("static"))
         .with_state(state)
+        .layer(AccessLogLayer)
+}
+
+/// The spoken-turn endpoint only exists when the `voice` feature is
+/// compiled in; otherwise it's routed to a 404 so `/api/tutor/voice/...`
+/// doesn't silently succeed against a build that can't transcribe anything.
+#[cfg(feature = "voice")]
+fn voice_route() -> axum::routing::MethodRouter<AppState> {
+    post(tutor_voice_message)
+}
+
+#[cfg(not(feature = "voice"))]
+fn voice_route() -> axum::routing::MethodRouter<AppState> {
+    post(|| async { StatusCode::NOT_FOUND })
 }
 
 async fn index_handler() -> &'static str {
@@ -83,6 +108,8 @@ struct ExecuteRequest {
     code: String,
     lesson_id: Option<String>,
     exercise_id: Option<String>,
+    #[serde(default)]
+    mode: RunMode,
 }
 
 async fn execute_code(
@@ -104,7 +131,7 @@ async fn execute_code(
 
         let result = state
             .code_executor
-            .execute_with_tests(&request.code, &exercise.test_code)
+            .execute_with_tests(&request.code, &exercise.test_code, request.mode)
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -157,3 +184,88 @@ async fn tutor_interact(
 
     Ok(Json(response))
 }
+
+/// Accepts a single chunk of raw microphone audio for a spoken student turn,
+/// transcribes it, and runs it through the same path as a typed message.
+/// Real-time streaming ASR happens against AWS Transcribe inside
+/// `InteractiveTutor::handle_voice_message`; here the request body is just
+/// wrapped as a one-item stream for it.
+#[cfg(feature = "voice")]
+async fn tutor_voice_message(
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+    body: axum::body::Bytes,
+) -> Result<Json<TutorResponse>, (StatusCode, String)> {
+    let audio = futures::stream::once(async move { anyhow::Result::<_>::Ok(body) }).boxed();
+
+    let response = state
+        .interactive_tutor
+        .handle_voice_message(session_id, audio)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(response))
+}
+
+/// Server-sent event payloads shared by the streaming endpoints: a `delta`
+/// event per incremental chunk of text, followed by one `done` event
+/// carrying the fully parsed JSON object.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", content = "data")]
+enum StreamPayload<T> {
+    #[serde(rename = "delta")]
+    Delta(String),
+    #[serde(rename = "done")]
+    Done(T),
+}
+
+async fn review_code_stream(
+    State(state): State<AppState>,
+    Json(request): Json<ReviewRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let lesson = state
+        .lesson_manager
+        .get_lesson(&request.lesson_id)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, "Lesson not found".to_string()))?;
+
+    let events = state
+        .ai_assistant
+        .review_code_stream(&request.code, lesson)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let sse_stream = events.map(|event| {
+        let payload = match event {
+            Ok(ReviewStreamEvent::Delta(text)) => StreamPayload::Delta(text),
+            Ok(ReviewStreamEvent::Done(review)) => StreamPayload::Done(*review),
+            Err(e) => StreamPayload::Delta(format!("[stream error: {}]", e)),
+        };
+        Ok(Event::default().json_data(payload).unwrap_or_default())
+    });
+
+    Ok(Sse::new(sse_stream))
+}
+
+async fn tutor_interact_stream(
+    State(state): State<AppState>,
+    Json(request): Json<TutorRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let events = state
+        .interactive_tutor
+        .clone()
+        .handle_request_stream(request)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let sse_stream = events.map(|event| {
+        let payload = match event {
+            Ok(TutorStreamEvent::Delta(text)) => StreamPayload::Delta(text),
+            Ok(TutorStreamEvent::Done(response)) => StreamPayload::Done(*response),
+            Err(e) => StreamPayload::Delta(format!("[stream error: {}]", e)),
+        };
+        Ok(Event::default().json_data(payload).unwrap_or_default())
+    });
+
+    Ok(Sse::new(sse_stream))
+}